@@ -0,0 +1,70 @@
+use std::{collections::BTreeMap, fs::File, path::Path};
+
+use eyre::Context;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::{
+    hl_gossip_config::HyperliquidChain,
+    hl_visor_config::VisorSetup,
+    seed_source::{SeedPeerSource, builtin_seed_sources},
+};
+
+/// Operator-provided per-chain configuration, keyed by chain name
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct ChainConfigs(BTreeMap<String, ChainConfig>);
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ChainConfig {
+    /// Whether to include seed peer sources hl-bootstrap ships with
+    #[serde(default = "default_true")]
+    pub builtin_seed_sources: bool,
+    /// Additional seed peer sources
+    #[serde(default)]
+    pub seed_sources: Vec<SeedPeerSource>,
+    /// hl-visor to install & run instead of the one in PATH, for chains the image does not ship hl-visor for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visor: Option<VisorSetup>,
+}
+
+const fn default_true() -> bool {
+    true
+}
+
+impl ChainConfigs {
+    pub fn load<P: AsRef<Path>>(path: P) -> eyre::Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .wrap_err_with(|| format!("failed to open chain config at {path:?}"))?;
+
+        let configs: Self = serde_json::from_reader(file)
+            .wrap_err_with(|| format!("failed to parse chain config at {path:?}"))?;
+        debug!(?path, chains = ?configs.0.keys(), "loaded chain config");
+
+        Ok(configs)
+    }
+
+    pub fn get(&self, chain: &HyperliquidChain) -> Option<&ChainConfig> {
+        let name = chain.to_string();
+        self.0
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(&name))
+            .map(|(_, config)| config)
+    }
+
+    /// Seed peer sources to query for given chain
+    pub fn seed_sources(&self, chain: &HyperliquidChain) -> Vec<SeedPeerSource> {
+        let Some(config) = self.get(chain) else {
+            return builtin_seed_sources(chain);
+        };
+
+        let mut sources = Vec::new();
+        if config.builtin_seed_sources {
+            sources.extend(builtin_seed_sources(chain));
+        }
+        sources.extend(config.seed_sources.iter().cloned());
+
+        sources
+    }
+}
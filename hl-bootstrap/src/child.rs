@@ -0,0 +1,357 @@
+use std::{
+    ffi::OsString,
+    os::unix::process::ExitStatusExt,
+    path::Path,
+    process::{ExitStatus, Stdio},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use clap::ValueEnum;
+use eyre::Context;
+use nix::{
+    sys::signal::{Signal, killpg},
+    unistd::Pid,
+};
+use tokio::{
+    process::{Child, Command},
+    signal::unix::{Signal as UnixSignal, SignalKind, signal},
+    sync::mpsc,
+    task::JoinHandle,
+    time::{Instant, sleep, timeout},
+};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    child_output::forward_child_output,
+    monitor::{COUNTER_HL_VISOR_RESTARTS, GAUGE_HL_VISOR_LAST_EXIT_CODE},
+};
+
+/// How long forwarders are given to log what's left in the output pipes after hl-visor exits
+const OUTPUT_FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Incremented whenever hl-visor gets started, so that whoever tracks its progress can tell runs apart
+pub static CHILD_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug)]
+pub enum ChildControl {
+    /// Gracefully stop hl-visor & start it again
+    Restart { reason: &'static str },
+}
+
+/// When hl-visor gets started again after it exits on its own
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum RestartPolicy {
+    /// Exit along with hl-visor, leaving restarts to the container runtime
+    #[default]
+    Never,
+    /// Restart when hl-visor exits with non-zero status or gets killed by a signal
+    OnFailure,
+    /// Restart whenever hl-visor exits
+    Always,
+}
+
+impl RestartPolicy {
+    fn should_restart(&self, status: &ExitStatus) -> bool {
+        match self {
+            Self::Never => false,
+            Self::OnFailure => !status.success(),
+            Self::Always => true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SupervisorOptions {
+    pub restart_policy: RestartPolicy,
+    /// Delay before the first restart, doubled for every consecutive crash
+    pub backoff_initial: Duration,
+    pub backoff_max: Duration,
+    /// Runs shorter than this count as crashes, longer ones reset the backoff
+    pub min_uptime: Duration,
+    /// Consecutive crashes after which hl-visor is considered crash looping & supervisor gives up
+    pub crash_loop_threshold: u32,
+    /// How long hl-visor is given to exit after SIGTERM before it gets killed
+    pub stop_timeout: Duration,
+    /// Whether to pipe hl-visor's output & re-emit it as tracing events
+    pub capture_output: bool,
+}
+
+impl SupervisorOptions {
+    fn backoff(&self, crashes: u32) -> Duration {
+        self.backoff_initial
+            .saturating_mul(2u32.saturating_pow(crashes.saturating_sub(1)))
+            .min(self.backoff_max)
+    }
+}
+
+/// Signals forwarded to hl-visor. SIGTERM & SIGINT stop it for good, SIGHUP is just passed on
+struct ForwardedSignals {
+    sigterm: UnixSignal,
+    sigint: UnixSignal,
+    sighup: UnixSignal,
+}
+
+impl ForwardedSignals {
+    fn new() -> eyre::Result<Self> {
+        let listen = |kind| signal(kind).wrap_err("failed to set up signal handler");
+        Ok(Self {
+            sigterm: listen(SignalKind::terminate())?,
+            sigint: listen(SignalKind::interrupt())?,
+            sighup: listen(SignalKind::hangup())?,
+        })
+    }
+
+    async fn recv(&mut self) -> Signal {
+        tokio::select! {
+            _ = self.sigterm.recv() => Signal::SIGTERM,
+            _ = self.sigint.recv() => Signal::SIGINT,
+            _ = self.sighup.recv() => Signal::SIGHUP,
+        }
+    }
+}
+
+/// Runs hl-visor, restarting it on request & according to restart policy. `on_crash` is awaited before restarting
+/// hl-visor which exited within minimum uptime.
+pub async fn run_child(
+    program: &Path,
+    args: &[OsString],
+    mut control: mpsc::Receiver<ChildControl>,
+    options: SupervisorOptions,
+    mut on_crash: impl AsyncFnMut(),
+) -> eyre::Result<ExitStatus> {
+    let mut signals = ForwardedSignals::new()?;
+    let mut crashes = 0;
+
+    'supervisor: loop {
+        let started = Instant::now();
+        let (mut child, forwarders) = spawn_child(program, args, options.capture_output)?;
+        CHILD_GENERATION.fetch_add(1, Ordering::Relaxed);
+
+        let status = loop {
+            tokio::select! {
+                status = child.wait() => break status.wrap_err("failed to wait for child")?,
+                Some(ChildControl::Restart { reason }) = control.recv() => {
+                    info!(reason, "restarting hl-visor");
+                    let status = stop_child(&mut child, Signal::SIGTERM, options.stop_timeout).await?;
+                    flush_child_output(forwarders).await;
+                    info!(?status, "hl-visor stopped");
+                    COUNTER_HL_VISOR_RESTARTS.with_label_values(&[reason]).inc();
+                    continue 'supervisor;
+                }
+                signal = signals.recv() => {
+                    if signal == Signal::SIGHUP {
+                        info!(?signal, "forwarding signal to hl-visor");
+                        signal_child(&child, signal);
+                        continue;
+                    }
+
+                    info!(?signal, stop_timeout = ?options.stop_timeout, "stopping hl-visor");
+                    let status = stop_child(&mut child, signal, options.stop_timeout).await?;
+                    flush_child_output(forwarders).await;
+                    info!(?status, "hl-visor stopped");
+                    GAUGE_HL_VISOR_LAST_EXIT_CODE.set(exit_code(&status).into());
+                    return Ok(status);
+                }
+            }
+        };
+        flush_child_output(forwarders).await;
+
+        let uptime = started.elapsed();
+        GAUGE_HL_VISOR_LAST_EXIT_CODE.set(exit_code(&status).into());
+        if !options.restart_policy.should_restart(&status) {
+            info!(?status, ?uptime, "hl-visor exited");
+            return Ok(status);
+        }
+
+        if uptime < options.min_uptime {
+            crashes += 1;
+        } else {
+            crashes = 0;
+        }
+        if crashes >= options.crash_loop_threshold {
+            // Giving up, exiting with hl-visor's status keeps it visible to whatever runs hl-bootstrap
+            error!(
+                ?status,
+                ?uptime,
+                crashes,
+                min_uptime = ?options.min_uptime,
+                "hl-visor is crash looping, not restarting it"
+            );
+            return Ok(status);
+        }
+
+        let backoff = options.backoff(crashes);
+        warn!(
+            ?status,
+            ?uptime,
+            crashes,
+            ?backoff,
+            "hl-visor exited, restarting"
+        );
+        if crashes > 0 {
+            on_crash().await;
+        }
+        tokio::select! {
+            _ = sleep(backoff) => {}
+            signal = signals.recv() => {
+                info!(?signal, "hl-visor is not running, not restarting it");
+                return Ok(status);
+            }
+        }
+        COUNTER_HL_VISOR_RESTARTS
+            .with_label_values(&["exited"])
+            .inc();
+    }
+}
+
+/// Exit code as shell reports it, 128 + signal number for processes killed by a signal
+pub fn exit_code(status: &ExitStatus) -> i32 {
+    match (status.code(), status.signal()) {
+        (Some(code), _) => code,
+        (None, Some(signal)) => 128 + signal,
+        (None, None) => 1,
+    }
+}
+
+/// Spawns hl-visor, along with tasks forwarding its output when captured
+fn spawn_child(
+    program: &Path,
+    args: &[OsString],
+    capture_output: bool,
+) -> eyre::Result<(Child, Vec<JoinHandle<()>>)> {
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        // Own process group, so hl-node gets signalled along with hl-visor
+        .process_group(0)
+        .kill_on_drop(true);
+    if capture_output {
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    }
+
+    let mut child = command.spawn().wrap_err("failed to spawn child")?;
+    let mut forwarders = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        forwarders.push(tokio::spawn(forward_child_output("stdout", stdout)));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forwarders.push(tokio::spawn(forward_child_output("stderr", stderr)));
+    }
+
+    Ok((child, forwarders))
+}
+
+/// Waits for forwarders to log hl-visor's trailing output. Processes which outlived hl-visor might keep the pipes open,
+/// in which case forwarders are left running in the background.
+async fn flush_child_output(mut forwarders: Vec<JoinHandle<()>>) {
+    let flushed = timeout(OUTPUT_FLUSH_TIMEOUT, async {
+        for forwarder in &mut forwarders {
+            let _ = forwarder.await;
+        }
+    })
+    .await;
+    if flushed.is_err() {
+        debug!("hl-visor output is still open, not waiting for it");
+    }
+}
+
+fn signal_child(child: &Child, signal: Signal) {
+    if let Some(pid) = child.id() {
+        let pgid = Pid::from_raw(pid as i32);
+        if let Err(err) = killpg(pgid, signal) {
+            warn!(?err, ?pgid, ?signal, "failed to signal hl-visor");
+        }
+    }
+}
+
+/// Sends the signal to child's process group, killing it if it does not exit within the timeout
+async fn stop_child(
+    child: &mut Child,
+    signal: Signal,
+    stop_timeout: Duration,
+) -> eyre::Result<ExitStatus> {
+    signal_child(child, signal);
+
+    match timeout(stop_timeout, child.wait()).await {
+        Ok(status) => status.wrap_err("failed to wait for child"),
+        Err(_) => {
+            warn!(?stop_timeout, "hl-visor did not stop in time, killing it");
+            if let Some(pid) = child.id()
+                && let Err(err) = killpg(Pid::from_raw(pid as i32), Signal::SIGKILL)
+            {
+                warn!(?err, "failed to send SIGKILL to hl-visor");
+            }
+            child.wait().await.wrap_err("failed to wait for child")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let options = SupervisorOptions {
+            restart_policy: RestartPolicy::OnFailure,
+            backoff_initial: Duration::from_secs(1),
+            backoff_max: Duration::from_secs(60),
+            min_uptime: Duration::from_secs(60),
+            crash_loop_threshold: 5,
+            stop_timeout: Duration::from_secs(30),
+            capture_output: false,
+        };
+        assert_eq!(options.backoff(0), Duration::from_secs(1));
+        assert_eq!(options.backoff(1), Duration::from_secs(1));
+        assert_eq!(options.backoff(3), Duration::from_secs(4));
+        assert_eq!(options.backoff(100), Duration::from_secs(60));
+    }
+
+    #[tokio::test]
+    async fn test_crash_loop() {
+        let (_control_tx, control_rx) = mpsc::channel(1);
+        let options = SupervisorOptions {
+            restart_policy: RestartPolicy::Always,
+            backoff_initial: Duration::from_millis(1),
+            backoff_max: Duration::from_millis(10),
+            min_uptime: Duration::from_secs(60),
+            crash_loop_threshold: 3,
+            stop_timeout: Duration::from_secs(30),
+            capture_output: false,
+        };
+
+        let mut crashes = 0;
+        let status = run_child(Path::new("false"), &[], control_rx, options, async || {
+            crashes += 1
+        })
+        .await;
+        assert_eq!(exit_code(&status.unwrap()), 1);
+        assert_eq!(crashes, 2);
+        assert_eq!(GAUGE_HL_VISOR_LAST_EXIT_CODE.get(), 1);
+
+        let (_control_tx, control_rx) = mpsc::channel(1);
+        let options = SupervisorOptions {
+            restart_policy: RestartPolicy::OnFailure,
+            ..options
+        };
+        let status = run_child(Path::new("true"), &[], control_rx, options, async || {}).await;
+        assert!(status.unwrap().success());
+    }
+
+    #[tokio::test]
+    async fn test_stop_child_timeout() -> eyre::Result<()> {
+        let (mut child, _) = spawn_child(
+            Path::new("sh"),
+            &["-c".into(), "trap '' TERM; sleep 10".into()],
+            true,
+        )?;
+        // Give shell time to set up the trap
+        sleep(Duration::from_millis(200)).await;
+
+        let status = stop_child(&mut child, Signal::SIGTERM, Duration::from_millis(200)).await?;
+        assert_eq!(exit_code(&status), 128 + Signal::SIGKILL as i32);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::HashSet;
+
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tracing::{Level, debug, error, info, trace, warn};
+
+/// Output line pattern hl-visor or hl-node is known to print when failing for a reason outside of its control
+pub struct KnownFailure {
+    pub name: &'static str,
+    pub pattern: &'static str,
+    pub hint: &'static str,
+}
+
+pub const KNOWN_FAILURES: &[KnownFailure] = &[
+    KnownFailure {
+        name: "ipv6_enabled",
+        pattern: "Could not parse home public ip",
+        hint: "hl-node resolved an IPv6 public address, disable IPv6 (sysctl net.ipv6.conf.all.disable_ipv6=1) for the container",
+    },
+    KnownFailure {
+        name: "address_in_use",
+        pattern: "Address already in use",
+        hint: "another process already listens on gossip (4000-4010) or RPC (3001) ports, make sure only one node runs in the network namespace",
+    },
+    KnownFailure {
+        name: "no_space",
+        pattern: "No space left on device",
+        hint: "data directory is full, enable pruning with HL_BOOTSTRAP_PRUNE_DATA_INTERVAL or grow the volume",
+    },
+    KnownFailure {
+        name: "too_many_open_files",
+        pattern: "Too many open files",
+        hint: "open file limit is too low, raise it (e.g. docker run --ulimit nofile=1048576)",
+    },
+];
+
+pub fn known_failure(line: &str) -> Option<&'static KnownFailure> {
+    KNOWN_FAILURES
+        .iter()
+        .find(|failure| line.contains(failure.pattern))
+}
+
+/// Guesses line's log level from the first few uppercase words, treating panics as errors
+pub fn parse_level(line: &str) -> Option<Level> {
+    if line.contains("panicked at") {
+        return Some(Level::ERROR);
+    }
+
+    line.split_whitespace().take(4).find_map(|word| {
+        match word.trim_matches(|c: char| !c.is_ascii_alphabetic()) {
+            "ERROR" => Some(Level::ERROR),
+            "WARN" | "WARNING" => Some(Level::WARN),
+            "INFO" => Some(Level::INFO),
+            "DEBUG" => Some(Level::DEBUG),
+            "TRACE" => Some(Level::TRACE),
+            _ => None,
+        }
+    })
+}
+
+/// Consecutive read errors after which output is no longer forwarded
+const MAX_READ_ERRORS: u32 = 16;
+
+/// Re-emits child's output line by line as tracing events, hinting at remediation of known failures once per stream.
+/// Keeps reading until the child closes the pipe, so the child never writes into a closed one.
+pub async fn forward_child_output(stream: &'static str, output: impl AsyncRead + Unpin) {
+    let mut output = BufReader::new(output);
+    let mut buf = Vec::new();
+    let mut hinted = HashSet::new();
+    let mut errors = 0;
+
+    loop {
+        buf.clear();
+        match output.read_until(b'\n', &mut buf).await {
+            Ok(0) => break,
+            Ok(_) => errors = 0,
+            Err(err) => {
+                warn!(?err, stream, "failed to read hl-visor output");
+                errors += 1;
+                if errors >= MAX_READ_ERRORS {
+                    break;
+                }
+                if buf.is_empty() {
+                    continue;
+                }
+            }
+        }
+
+        // Non-UTF-8 output is still forwarded, with invalid sequences replaced
+        let line = String::from_utf8_lossy(&buf);
+        let line = strip_ansi(line.trim_end_matches(['\n', '\r']));
+        let failure = known_failure(&line);
+        let level = match failure {
+            Some(_) => Level::ERROR,
+            None => parse_level(&line).unwrap_or(Level::INFO),
+        };
+        match level {
+            Level::ERROR => error!(target: "hl_visor", stream, "{line}"),
+            Level::WARN => warn!(target: "hl_visor", stream, "{line}"),
+            Level::INFO => info!(target: "hl_visor", stream, "{line}"),
+            Level::DEBUG => debug!(target: "hl_visor", stream, "{line}"),
+            Level::TRACE => trace!(target: "hl_visor", stream, "{line}"),
+        }
+
+        if let Some(failure) = failure
+            && hinted.insert(failure.name)
+        {
+            error!(
+                failure = failure.name,
+                hint = failure.hint,
+                "hl-visor hit a known failure"
+            );
+        }
+    }
+}
+
+/// Drops terminal color sequences, which would end up escaped in structured logs
+fn strip_ansi(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // CSI sequences end with a letter, e.g. `\x1b[31m`
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncWriteExt, duplex};
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(
+            parse_level("2025-06-13T19:59:46.930Z WARN node: slow block"),
+            Some(Level::WARN)
+        );
+        assert_eq!(parse_level("[ERROR] failed"), Some(Level::ERROR));
+        assert_eq!(
+            parse_level(&strip_ansi("\x1b[32m INFO\x1b[0m visor: started")),
+            Some(Level::INFO)
+        );
+        assert_eq!(
+            parse_level(
+                "thread 'tokio-runtime-worker' panicked at /home/ubuntu/hl/code_Mainnet/node/src/node.rs:487:6:"
+            ),
+            Some(Level::ERROR)
+        );
+        assert_eq!(parse_level("no error in downloaded binary"), None);
+    }
+
+    #[test]
+    fn test_known_failure() {
+        let line = "Could not parse home public ip: sleep_retry retried home_node_public_ip for sleep times \
+                    [Duration(1.0), Duration(2.0), Duration(4.0)] last err invalid IPv4 address syntax";
+        assert_eq!(known_failure(line).unwrap().name, "ipv6_enabled");
+        assert_eq!(
+            known_failure(
+                "Error: Os { code: 98, kind: AddrInUse, message: \"Address already in use\" }"
+            )
+            .unwrap()
+            .name,
+            "address_in_use"
+        );
+        assert!(known_failure("applied block 628178000").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_forward_non_utf8_output() {
+        let (mut writer, reader) = duplex(16);
+        let forwarder = tokio::spawn(forward_child_output("stdout", reader));
+
+        writer.write_all(b"\xff\xfe invalid utf-8\n").await.unwrap();
+        for idx in 0..100 {
+            writer
+                .write_all(format!("line {idx}\n").as_bytes())
+                .await
+                .unwrap();
+        }
+        drop(writer);
+
+        forwarder.await.unwrap();
+    }
+}
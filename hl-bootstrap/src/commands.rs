@@ -0,0 +1,490 @@
+use std::{collections::BTreeMap, fs, net::Ipv4Addr, path::PathBuf, time::Duration};
+
+use clap::Subcommand;
+use eyre::{Context, bail};
+use serde::Serialize;
+
+use crate::{
+    Cli,
+    crashes::{CrashScanner, group_crashes},
+    doctor::{CheckStatus, run_checks},
+    gossip_diff::GossipConfigDiff,
+    gossip_validation::{Severity, validate_gossip_config},
+    hl_gossip_config::{MergePolicies, OverrideGossipConfig},
+    monitor::request_exchange_time,
+    peer_db::{PeerDb, unix_now},
+    prune::run_cleanup,
+    seed_peers::{
+        SeedPeersOptions, discover_seed_peers, merge_existing_gossip_config,
+        pick_gossip_seed_peers, private_gossip_config, seed_peers_gossip_config,
+    },
+    selection::select_seed_peers,
+    speedtest::{SeedPeerMeasurement, measure_seed_peers},
+};
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum Command {
+    /// Seed peer candidates
+    #[command(subcommand)]
+    Peers(PeersCommand),
+    /// Measure latency to seed peer candidates & show which ones would be picked
+    Speedtest {
+        #[arg(long)]
+        json: bool,
+        /// Record measurements in the peer database, like hl-bootstrap does on startup
+        #[arg(long)]
+        record: bool,
+    },
+    /// Remove node data older than --prune-data-older-than
+    Prune {
+        /// Only list files which would be removed
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// override_gossip_config.json
+    #[command(subcommand)]
+    GossipConfig(GossipConfigCommand),
+    /// Check override_gossip_config.json against hl-node constraints
+    Validate {
+        /// Defaults to --override-gossip-config-path
+        path: Option<PathBuf>,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Summarize latest hl-node crashes found in visor child stderr files
+    Crashes {
+        /// How many crash signatures to show
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check runtime environment for common misconfigurations
+    Doctor {
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show node & hl-bootstrap state
+    Status {
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum PeersCommand {
+    /// List seed peer candidates per source
+    List {
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum GossipConfigCommand {
+    /// Discover & measure seed peers, printing resulting configuration without writing it
+    Render {
+        /// Merge with existing override_gossip_config.json like hl-bootstrap would
+        #[arg(long)]
+        merge: bool,
+    },
+    /// Dry run: discover & measure seed peers like on startup, showing how override_gossip_config.json would change
+    /// without writing it
+    Diff {
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub async fn run_command(command: &Command, args: &Cli) -> eyre::Result<()> {
+    match command {
+        Command::Peers(PeersCommand::List { json }) => list_peers(args, *json).await,
+        Command::Speedtest { json, record } => speedtest(args, *json, *record).await,
+        Command::Prune { dry_run } => prune(args, *dry_run).await,
+        Command::GossipConfig(GossipConfigCommand::Render { merge }) => {
+            render_gossip_config(args, *merge).await
+        }
+        Command::GossipConfig(GossipConfigCommand::Diff { json }) => {
+            diff_gossip_config(args, *json).await
+        }
+        Command::Validate { path, json } => validate(args, path.as_ref(), *json),
+        Command::Crashes { limit, json } => crashes(args, *limit, *json),
+        Command::Doctor { json } => doctor(args, *json),
+        Command::Status { json } => status(args, *json).await,
+    }
+}
+
+async fn list_peers(args: &Cli, json: bool) -> eyre::Result<()> {
+    #[derive(Serialize)]
+    struct Peer<'a> {
+        ip: Ipv4Addr,
+        operator_name: &'a str,
+        banned: bool,
+    }
+
+    let seed_peers = SeedPeersOptions {
+        dry_run: true,
+        ..args.seed_peers_options()?
+    };
+    let mut peer_db = PeerDb::load(&seed_peers.peer_db_path)?;
+    let candidates = discover_seed_peers(&seed_peers, &mut peer_db).await?;
+
+    let mut by_source: BTreeMap<&str, Vec<Peer>> = BTreeMap::new();
+    for candidate in &candidates {
+        for source in &candidate.sources {
+            by_source.entry(source).or_default().push(Peer {
+                ip: candidate.ip,
+                operator_name: &candidate.operator_name,
+                banned: peer_db.is_banned(&candidate.ip),
+            });
+        }
+    }
+    for peers in by_source.values_mut() {
+        peers.sort_by_key(|peer| peer.ip);
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&by_source)?);
+        return Ok(());
+    }
+
+    for (source, peers) in by_source {
+        println!("{source} ({} peers)", peers.len());
+        for peer in peers {
+            println!(
+                "  {:<15}  {:<32}  {}",
+                peer.ip,
+                peer.operator_name,
+                if peer.banned { "banned" } else { "" }
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn speedtest(args: &Cli, json: bool, record: bool) -> eyre::Result<()> {
+    #[derive(Serialize)]
+    struct Measurement<'a> {
+        ip: Ipv4Addr,
+        operator_name: &'a str,
+        sources: &'a [String],
+        picked: bool,
+        port: u16,
+        latency_ms: f64,
+        min_ms: f64,
+        median_ms: f64,
+        p90_ms: f64,
+        jitter_ms: f64,
+        kernel_rtt_ms: Option<f64>,
+        lost: usize,
+        score_ms: f64,
+    }
+
+    let seed_peers = SeedPeersOptions {
+        dry_run: !record,
+        ..args.seed_peers_options()?
+    };
+    let mut peer_db = PeerDb::load(&seed_peers.peer_db_path)?;
+    let candidates = discover_seed_peers(&seed_peers, &mut peer_db).await?;
+    let measurements = measure_seed_peers(candidates, seed_peers.speedtest, &mut peer_db).await?;
+    let picked = select_seed_peers(&measurements, seed_peers.amount, seed_peers.selection);
+    if record {
+        peer_db.save()?;
+    }
+
+    let ms = |duration: Duration| duration.as_secs_f64() * 1e3;
+    let rows: Vec<_> = measurements
+        .iter()
+        .map(|measurement| {
+            let (peer, stats) = (&measurement.peer, &measurement.stats);
+            Measurement {
+                ip: peer.ip,
+                operator_name: &peer.operator_name,
+                sources: &peer.sources,
+                picked: picked.iter().any(|picked| picked.peer.ip == peer.ip),
+                port: stats.port,
+                latency_ms: ms(measurement.latency),
+                min_ms: ms(stats.min),
+                median_ms: ms(stats.median),
+                p90_ms: ms(stats.p90),
+                jitter_ms: ms(stats.jitter),
+                kernel_rtt_ms: stats.kernel_rtt.map(ms),
+                lost: stats.lost,
+                score_ms: ms(measurement.score),
+            }
+        })
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+
+    println!(
+        "{:<6}  {:<15}  {:<24}  {:>5}  {:>8}  {:>8}  {:>8}  {:>8}  {:>8}  {:>4}  {:>8}",
+        "PICKED",
+        "IP",
+        "OPERATOR",
+        "PORT",
+        "MIN",
+        "MEDIAN",
+        "P90",
+        "JITTER",
+        "KRTT",
+        "LOST",
+        "SCORE"
+    );
+    for row in rows {
+        println!(
+            "{:<6}  {:<15}  {:<24.24}  {:>5}  {:>8.2}  {:>8.2}  {:>8.2}  {:>8.2}  {:>8}  {:>4}  {:>8.2}",
+            if row.picked { "*" } else { "" },
+            row.ip,
+            row.operator_name,
+            row.port,
+            row.min_ms,
+            row.median_ms,
+            row.p90_ms,
+            row.jitter_ms,
+            row.kernel_rtt_ms
+                .map(|rtt| format!("{rtt:.2}"))
+                .unwrap_or_else(|| "-".to_string()),
+            row.lost,
+            row.score_ms,
+        );
+    }
+
+    Ok(())
+}
+
+async fn prune(args: &Cli, dry_run: bool) -> eyre::Result<()> {
+    let data_path = args.data_directory()?.join("hl/data");
+    let files = run_cleanup(&data_path, args.prune_data_older_than.into(), dry_run).await?;
+
+    for file in files {
+        println!("{}", file.display());
+    }
+
+    Ok(())
+}
+
+/// Gossip configuration hl-bootstrap would write on startup, before merging with existing one
+async fn build_gossip_config(
+    args: &Cli,
+    seed_peers: &SeedPeersOptions,
+) -> eyre::Result<(
+    OverrideGossipConfig,
+    MergePolicies,
+    Vec<SeedPeerMeasurement>,
+)> {
+    Ok(match args.upstream_sentry_ips() {
+        Some(sentry_ips) => {
+            let (config, policies) = private_gossip_config(seed_peers, sentry_ips);
+            (config, policies, Vec::new())
+        }
+        None => {
+            let seed_nodes = pick_gossip_seed_peers(seed_peers).await?;
+            let (config, policies) = seed_peers_gossip_config(
+                seed_peers,
+                seed_nodes
+                    .iter()
+                    .map(|measurement| measurement.peer.clone()),
+            );
+            (config, policies, seed_nodes)
+        }
+    })
+}
+
+async fn render_gossip_config(args: &Cli, merge: bool) -> eyre::Result<()> {
+    let seed_peers = SeedPeersOptions {
+        dry_run: true,
+        ..args.seed_peers_options()?
+    };
+    let (config, policies, _) = build_gossip_config(args, &seed_peers).await?;
+
+    let config = if merge {
+        merge_existing_gossip_config(&seed_peers, config, &policies)?.1
+    } else {
+        config
+    };
+    println!("{}", serde_json::to_string(&config)?);
+
+    Ok(())
+}
+
+async fn diff_gossip_config(args: &Cli, json: bool) -> eyre::Result<()> {
+    let seed_peers = SeedPeersOptions {
+        dry_run: true,
+        ..args.seed_peers_options()?
+    };
+    let (config, policies, seed_nodes) = build_gossip_config(args, &seed_peers).await?;
+    let (existing, config) = merge_existing_gossip_config(&seed_peers, config, &policies)?;
+
+    let peer_db = PeerDb::load(&seed_peers.peer_db_path)?;
+    let diff = GossipConfigDiff::new(existing.as_ref(), &config, &seed_nodes, &peer_db);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else if existing.is_none() {
+        print!(
+            "{} does not exist yet\n{diff}",
+            args.override_gossip_config_path.display()
+        );
+    } else if diff.is_empty() {
+        print!("no changes\n{diff}");
+    } else {
+        print!("{diff}");
+    }
+
+    Ok(())
+}
+
+fn validate(args: &Cli, path: Option<&PathBuf>, json: bool) -> eyre::Result<()> {
+    let path = path.unwrap_or(&args.override_gossip_config_path);
+    let content = fs::read(path).wrap_err_with(|| format!("failed to read {path:?}"))?;
+    let config: OverrideGossipConfig =
+        serde_json::from_slice(&content).wrap_err_with(|| format!("failed to parse {path:?}"))?;
+
+    let issues = validate_gossip_config(&config, args.visor_chain(&config.chain).as_ref());
+    if json {
+        println!("{}", serde_json::to_string_pretty(&issues)?);
+    } else if issues.is_empty() {
+        println!("{}: ok", path.display());
+    } else {
+        for issue in &issues {
+            println!("{}: {issue}", path.display());
+        }
+    }
+
+    if issues.iter().any(|issue| issue.severity == Severity::Error) {
+        bail!("{path:?} is not valid");
+    }
+
+    Ok(())
+}
+
+fn crashes(args: &Cli, limit: usize, json: bool) -> eyre::Result<()> {
+    let crashes = CrashScanner::default().scan(args.data_directory()?);
+    let mut groups = group_crashes(&crashes);
+    groups.truncate(limit);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&groups)?);
+        return Ok(());
+    }
+
+    if groups.is_empty() {
+        println!("no crashes found");
+        return Ok(());
+    }
+
+    let now = unix_now();
+    for group in groups {
+        println!(
+            "[{}]  {}x  hardfork {}  last {}s ago  {}",
+            group.id,
+            group.count,
+            group.hardfork,
+            now.saturating_sub(group.last_seen),
+            group.latest.panic.location,
+        );
+        for line in group.latest.panic.message.lines() {
+            println!("    {line}");
+        }
+        println!("    ({})", group.latest.file.display());
+    }
+
+    Ok(())
+}
+
+fn doctor(args: &Cli, json: bool) -> eyre::Result<()> {
+    let results = run_checks(args);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else {
+        for result in &results {
+            println!("{result}");
+        }
+    }
+
+    if results
+        .iter()
+        .any(|result| result.status == CheckStatus::Fail)
+    {
+        bail!("some checks failed");
+    }
+
+    Ok(())
+}
+
+async fn status(args: &Cli, json: bool) -> eyre::Result<()> {
+    #[derive(Serialize)]
+    struct Status {
+        node_responding: bool,
+        node_drift_ms: Option<u64>,
+        gossip_config_age_secs: Option<u64>,
+        root_node_ips: Vec<Ipv4Addr>,
+        reserved_peer_ips: Vec<Ipv4Addr>,
+        try_new_peers: Option<bool>,
+        known_peers: usize,
+        banned_peers: usize,
+    }
+
+    let node_drift_ms = request_exchange_time()
+        .await
+        .ok()
+        .map(|time| (unix_now() * 1000).saturating_sub(time));
+
+    // Not using load_override_gossip_config, which moves invalid configs aside
+    let gossip_config = fs::read(&args.override_gossip_config_path)
+        .ok()
+        .and_then(|content| serde_json::from_slice::<OverrideGossipConfig>(&content).ok());
+    let gossip_config_age_secs = fs::metadata(&args.override_gossip_config_path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .map(|modified| modified.elapsed().unwrap_or_default().as_secs());
+
+    let peer_db = PeerDb::load(args.peer_db_path()?)?;
+    let status = Status {
+        node_responding: node_drift_ms.is_some(),
+        node_drift_ms,
+        gossip_config_age_secs,
+        root_node_ips: gossip_config
+            .iter()
+            .flat_map(|config| config.root_node_ips.iter().map(|node| node.ip))
+            .collect(),
+        reserved_peer_ips: gossip_config
+            .iter()
+            .flat_map(|config| config.reserved_peer_ips.iter().copied())
+            .collect(),
+        try_new_peers: gossip_config.as_ref().map(|config| config.try_new_peers),
+        known_peers: peer_db.ips().count(),
+        banned_peers: peer_db.ips().filter(|ip| peer_db.is_banned(ip)).count(),
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&status)?);
+        return Ok(());
+    }
+
+    match status.node_drift_ms {
+        Some(drift) => println!("node: responding, {drift}ms behind"),
+        None => println!("node: not responding"),
+    }
+    match status.gossip_config_age_secs {
+        Some(age) => println!(
+            "gossip config: {} root nodes, {} reserved peers, try_new_peers={}, written {age}s ago",
+            status.root_node_ips.len(),
+            status.reserved_peer_ips.len(),
+            status.try_new_peers.unwrap_or_default(),
+        ),
+        None => println!("gossip config: missing"),
+    }
+    println!(
+        "peer database: {} peers, {} banned",
+        status.known_peers, status.banned_peers
+    );
+
+    Ok(())
+}
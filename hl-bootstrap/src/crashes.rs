@@ -0,0 +1,368 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tokio::time::{MissedTickBehavior, interval};
+use tracing::{debug, warn};
+
+use crate::monitor::GAUGE_HL_NODE_CRASHES;
+
+/// Longest message kept in crash signatures, so metric labels stay reasonably sized
+const SIGNATURE_MESSAGE_LEN: usize = 80;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Panic {
+    pub thread: Option<String>,
+    /// Source location as `file:line:column`
+    pub location: String,
+    pub message: String,
+}
+
+impl Panic {
+    /// Location without the column & message with numbers masked, so the same crash at different heights or
+    /// timestamps groups together
+    pub fn signature(&self) -> String {
+        let location = match self.location.rsplit_once(':') {
+            Some((file_line, column)) if column.chars().all(|c| c.is_ascii_digit()) => file_line,
+            _ => &self.location,
+        };
+
+        // Digits within words (IPv4, code_Mainnet2) are kept
+        let mut message = String::new();
+        let (mut in_number, mut in_word) = (false, false);
+        for c in self.message.lines().next().unwrap_or_default().chars() {
+            if c.is_ascii_digit() && !in_word {
+                if !in_number {
+                    message.push('N');
+                }
+                in_number = true;
+            } else {
+                message.push(c);
+                in_word = c.is_alphanumeric() || c == '_';
+                in_number = false;
+            }
+        }
+        let message: String = message.chars().take(SIGNATURE_MESSAGE_LEN).collect();
+
+        format!("{location}: {message}")
+    }
+}
+
+/// Short stable ID of a crash signature, used as metric label instead of the unbounded signature text
+pub fn signature_id(signature: &str) -> String {
+    Sha256::digest(signature)
+        .iter()
+        .take(6)
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Crash {
+    pub file: PathBuf,
+    /// `<ymd>` directory
+    pub date: String,
+    /// `<hardfork_version>` directory
+    pub hardfork: String,
+    /// Unix timestamp in seconds of when the stderr file was last written
+    pub time: u64,
+    pub panic: Panic,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CrashGroup {
+    /// See [`signature_id`]
+    pub id: String,
+    pub signature: String,
+    pub hardfork: String,
+    pub count: usize,
+    pub first_seen: u64,
+    pub last_seen: u64,
+    /// Latest crash with this signature
+    pub latest: Crash,
+}
+
+/// Extracts panics from hl-visor child stderr, supporting both `panicked at <location>:\n<message>` and older
+/// `panicked at '<message>', <location>` formats
+pub fn parse_panics(content: &str) -> Vec<Panic> {
+    let mut panics = Vec::new();
+    let mut lines = content.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        let Some((prefix, rest)) = line.split_once("panicked at ") else {
+            continue;
+        };
+        let thread = prefix
+            .trim()
+            .strip_prefix("thread '")
+            .and_then(|thread| thread.strip_suffix('\''))
+            .map(str::to_string);
+
+        if let Some(quoted) = rest.strip_prefix('\'')
+            && let Some((message, location)) = quoted.rsplit_once("', ")
+        {
+            panics.push(Panic {
+                thread,
+                location: location.to_string(),
+                message: message.to_string(),
+            });
+            continue;
+        }
+
+        let location = rest.trim_end().trim_end_matches(':').to_string();
+        let mut message = Vec::new();
+        while let Some(line) = lines.peek() {
+            if line.trim().is_empty()
+                || line.starts_with("note: ")
+                || line.starts_with("stack backtrace:")
+                || line.contains("panicked at ")
+            {
+                break;
+            }
+            message.push(lines.next().unwrap_or_default());
+        }
+
+        panics.push(Panic {
+            thread,
+            location,
+            message: message.join("\n"),
+        });
+    }
+
+    panics
+}
+
+/// Parses stderr files, remembering results for files which have not changed since the last scan
+#[derive(Default)]
+pub struct CrashScanner {
+    parsed: HashMap<PathBuf, (SystemTime, u64, Vec<Panic>)>,
+}
+
+impl CrashScanner {
+    /// Finds panics in ${base}/hl/data/visor_child_stderr/<ymd>/<hardfork_version>/* files, oldest first
+    pub fn scan<P: AsRef<Path>>(&mut self, base_path: P) -> Vec<Crash> {
+        let stderr_path = base_path.as_ref().join("hl/data/visor_child_stderr");
+        let mut crashes = Vec::new();
+        let mut seen = HashSet::new();
+
+        for date_dir in read_dirs(&stderr_path) {
+            for hardfork_dir in read_dirs(&date_dir) {
+                let Ok(entries) = fs::read_dir(&hardfork_dir) else {
+                    continue;
+                };
+                for entry in entries.flatten() {
+                    let path = entry.path();
+                    let Ok(metadata) = entry.metadata() else {
+                        continue;
+                    };
+                    if !metadata.is_file() {
+                        continue;
+                    }
+                    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+
+                    let Some(panics) = self.parse_file(&path, modified, metadata.len()) else {
+                        continue;
+                    };
+                    seen.insert(path.clone());
+
+                    let name = |dir: &Path| {
+                        dir.file_name()
+                            .map(|name| name.to_string_lossy().into_owned())
+                            .unwrap_or_default()
+                    };
+                    let time = modified
+                        .duration_since(SystemTime::UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_secs();
+                    crashes.extend(panics.iter().map(|panic| Crash {
+                        file: path.clone(),
+                        date: name(&date_dir),
+                        hardfork: name(&hardfork_dir),
+                        time,
+                        panic: panic.clone(),
+                    }));
+                }
+            }
+        }
+
+        // Forget pruned files
+        self.parsed.retain(|path, _| seen.contains(path));
+
+        crashes.sort_by(|a, b| a.time.cmp(&b.time).then(a.file.cmp(&b.file)));
+        crashes
+    }
+
+    fn parse_file(&mut self, path: &Path, modified: SystemTime, len: u64) -> Option<&Vec<Panic>> {
+        let stale = self
+            .parsed
+            .get(path)
+            .is_none_or(|(parsed_modified, parsed_len, _)| {
+                *parsed_modified != modified || *parsed_len != len
+            });
+        if stale {
+            // Not read_to_string, as stderr might contain invalid UTF-8
+            let content = match fs::read(path) {
+                Ok(content) => content,
+                Err(err) => {
+                    warn!(?err, ?path, "failed to read visor child stderr");
+                    return None;
+                }
+            };
+            let panics = parse_panics(&String::from_utf8_lossy(&content));
+            self.parsed
+                .insert(path.to_path_buf(), (modified, len, panics));
+        }
+
+        self.parsed.get(path).map(|(_, _, panics)| panics)
+    }
+}
+
+fn read_dirs(path: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(path) else {
+        return Vec::new();
+    };
+
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Groups crashes by signature & hardfork version, most recent first
+pub fn group_crashes(crashes: &[Crash]) -> Vec<CrashGroup> {
+    let mut groups: BTreeMap<(String, &str), CrashGroup> = BTreeMap::new();
+    for crash in crashes {
+        let signature = crash.panic.signature();
+        groups
+            .entry((signature.clone(), &crash.hardfork))
+            .and_modify(|group| {
+                group.count += 1;
+                group.first_seen = group.first_seen.min(crash.time);
+                if crash.time >= group.last_seen {
+                    group.last_seen = crash.time;
+                    group.latest = crash.clone();
+                }
+            })
+            .or_insert_with(|| CrashGroup {
+                id: signature_id(&signature),
+                signature,
+                hardfork: crash.hardfork.clone(),
+                count: 1,
+                first_seen: crash.time,
+                last_seen: crash.time,
+                latest: crash.clone(),
+            });
+    }
+
+    let mut groups: Vec<_> = groups.into_values().collect();
+    groups.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+    groups
+}
+
+/// Worker task that periodically scans visor child stderr files, exporting crash counts as metrics
+pub async fn crash_scan_task<P: AsRef<Path>>(base_path: P, scan_interval: Duration) {
+    let mut scanner = CrashScanner::default();
+    let mut interval = interval(scan_interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    loop {
+        interval.tick().await;
+
+        let groups = group_crashes(&scanner.scan(&base_path));
+        debug!(groups = groups.len(), "scanned visor child stderr");
+
+        GAUGE_HL_NODE_CRASHES.reset();
+        for group in &groups {
+            GAUGE_HL_NODE_CRASHES
+                .with_label_values(&[group.id.as_str(), group.hardfork.as_str()])
+                .set(group.count as i64);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PANIC: &str = "\
+thread 'tokio-runtime-worker' panicked at /home/ubuntu/hl/code_Mainnet/node/src/node.rs:487:6:
+Could not parse home public ip: sleep_retry retried home_node_public_ip for sleep times [Duration(1.0), Duration(2.0), Duration(4.0)] last err invalid IPv4 address syntax
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+";
+
+    #[test]
+    fn test_parse_panics() {
+        let panics = parse_panics(&format!(
+            "some log line\n{PANIC}thread 'main' panicked at 'block 628178000 not found', src/state.rs:10:5\n"
+        ));
+        assert_eq!(panics.len(), 2);
+        assert_eq!(panics[0].thread.as_deref(), Some("tokio-runtime-worker"));
+        assert_eq!(
+            panics[0].location,
+            "/home/ubuntu/hl/code_Mainnet/node/src/node.rs:487:6"
+        );
+        assert!(
+            panics[0]
+                .message
+                .starts_with("Could not parse home public ip")
+        );
+        assert_eq!(panics[1].location, "src/state.rs:10:5");
+        assert_eq!(panics[1].signature(), "src/state.rs:10: block N not found");
+
+        let panic = Panic {
+            thread: None,
+            location: "src/node.rs:487:6".to_string(),
+            message: "invalid IPv4 address 10.0.0.256".to_string(),
+        };
+        assert_eq!(
+            panic.signature(),
+            "src/node.rs:487: invalid IPv4 address N.N.N.N"
+        );
+    }
+
+    #[test]
+    fn test_scan_crashes() -> eyre::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let stderr_path = temp_dir.path().join("hl/data/visor_child_stderr");
+        for (date, hardfork, file) in [
+            ("20250613", "1234", "1"),
+            ("20250614", "1234", "2"),
+            ("20250614", "1235", "3"),
+        ] {
+            fs::create_dir_all(stderr_path.join(date).join(hardfork))?;
+            fs::write(stderr_path.join(date).join(hardfork).join(file), PANIC)?;
+        }
+        fs::write(stderr_path.join("20250614/1235/4"), "clean exit\n")?;
+
+        let mut scanner = CrashScanner::default();
+        let crashes = scanner.scan(temp_dir.path());
+        assert_eq!(crashes.len(), 3);
+        assert_eq!(scanner.parsed.len(), 4);
+
+        let groups = group_crashes(&crashes);
+        assert_eq!(groups.len(), 2);
+        let group = groups
+            .iter()
+            .find(|group| group.hardfork == "1234")
+            .unwrap();
+        assert_eq!(group.count, 2);
+        assert_eq!(group.id.len(), 12);
+        assert_eq!(group.id, signature_id(&group.signature));
+        assert_eq!(group.latest.date, "20250614");
+
+        fs::remove_dir_all(stderr_path.join("20250613"))?;
+        assert_eq!(scanner.scan(temp_dir.path()).len(), 2);
+        assert_eq!(scanner.parsed.len(), 3);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,585 @@
+use std::{
+    fmt, fs,
+    net::{Ipv4Addr, TcpListener},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use eyre::bail;
+use nix::sys::{
+    resource::{Resource, getrlimit},
+    statvfs::statvfs,
+};
+use serde::Serialize;
+use tempfile::NamedTempFile;
+use tracing::{debug, error, warn};
+
+use crate::{
+    Cli,
+    sysctl::{SysctlScope, SysctlState, check_sysctls, in_container, ipv6_enabled},
+};
+
+const GIB: u64 = 1 << 30;
+
+/// Free space below which disk space check warns / fails
+const DISK_SPACE_WARN: u64 = 100 * GIB;
+const DISK_SPACE_FAIL: u64 = 10 * GIB;
+
+const INODES_FAIL: u64 = 100_000;
+
+/// Open file soft limit below which hl-node tends to run out of file descriptors
+const OPEN_FILES_WARN: u64 = 65_536;
+const OPEN_FILES_FAIL: u64 = 4_096;
+
+/// Non-validator machine specs, see https://github.com/hyperliquid-dex/node/blob/main/README.md#machine-specs
+const RECOMMENDED_MEMORY: u64 = 64 * GIB;
+const RECOMMENDED_CPUS: f64 = 16.0;
+
+/// Fingerprint of etc/hl-pubkey.asc, the key hl-visor & hl-node binaries are signed with
+const HYPERLIQUID_KEY_FINGERPRINT: &str = "CF2C2EA3DC3E8F042A55FB6503254A9349F1820B";
+
+/// Gossip ports & RPC port
+const NODE_PORTS: &[u16] = &[
+    4000, 4001, 4002, 4003, 4004, 4005, 4006, 4007, 4008, 4009, 4010, 3001,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    /// Node likely runs, but not as well as it could
+    Warn,
+    /// Node likely won't run properly
+    Fail,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CheckResult {
+    pub check: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl fmt::Display for CheckResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self.status {
+            CheckStatus::Ok => " ok ",
+            CheckStatus::Warn => "warn",
+            CheckStatus::Fail => "FAIL",
+        };
+        write!(f, "[{status}] {}: {}", self.check, self.detail)
+    }
+}
+
+type Outcome = (CheckStatus, String);
+
+pub struct Check {
+    pub name: &'static str,
+    run: fn(&Cli) -> Outcome,
+}
+
+/// All preflight checks, in the order they're run
+pub const CHECKS: &[Check] = &[
+    Check {
+        name: "ipv6",
+        run: check_ipv6,
+    },
+    Check {
+        name: "hl_visor",
+        run: check_hl_visor,
+    },
+    Check {
+        name: "network",
+        run: check_network,
+    },
+    Check {
+        name: "visor_chain",
+        run: check_visor_chain,
+    },
+    Check {
+        name: "data_directory",
+        run: check_data_directory,
+    },
+    Check {
+        name: "visor_directory",
+        run: check_visor_directory,
+    },
+    Check {
+        name: "disk_space",
+        run: check_disk_space,
+    },
+    Check {
+        name: "inodes",
+        run: check_inodes,
+    },
+    Check {
+        name: "open_files",
+        run: check_open_files,
+    },
+    Check {
+        name: "sysctls",
+        run: check_sysctls_recommended,
+    },
+    Check {
+        name: "memory",
+        run: check_memory,
+    },
+    Check {
+        name: "cpu",
+        run: check_cpu,
+    },
+    Check {
+        name: "ports",
+        run: check_ports,
+    },
+    Check {
+        name: "gpg_keyring",
+        run: check_gpg_keyring,
+    },
+];
+
+pub fn run_checks(args: &Cli) -> Vec<CheckResult> {
+    CHECKS
+        .iter()
+        .map(|check| {
+            let (status, detail) = (check.run)(args);
+            CheckResult {
+                check: check.name,
+                status,
+                detail,
+            }
+        })
+        .collect()
+}
+
+/// Runs all checks on startup, logging problems & failing when any of `fatal` checks fails
+pub fn preflight(args: &Cli, fatal: &[String]) -> eyre::Result<()> {
+    if let Some(unknown) = fatal
+        .iter()
+        .find(|name| !CHECKS.iter().any(|check| check.name == name.as_str()))
+    {
+        bail!(
+            "unknown doctor check {unknown:?}, known checks are {:?}",
+            CHECKS.iter().map(|check| check.name).collect::<Vec<_>>()
+        );
+    }
+
+    let mut failed = Vec::new();
+    for result in run_checks(args) {
+        let is_fatal = fatal.iter().any(|name| name == result.check);
+        match result.status {
+            CheckStatus::Ok => debug!(
+                check = result.check,
+                detail = result.detail,
+                "preflight check ok"
+            ),
+            CheckStatus::Warn => warn!(
+                check = result.check,
+                detail = result.detail,
+                "preflight check warning"
+            ),
+            CheckStatus::Fail if is_fatal => {
+                error!(
+                    check = result.check,
+                    detail = result.detail,
+                    "preflight check failed"
+                );
+                failed.push(result.check);
+            }
+            CheckStatus::Fail => warn!(
+                check = result.check,
+                detail = result.detail,
+                "preflight check failed"
+            ),
+        }
+    }
+
+    if !failed.is_empty() {
+        bail!("preflight checks failed: {}", failed.join(", "));
+    }
+
+    Ok(())
+}
+
+fn check_ipv6(args: &Cli) -> Outcome {
+    match ipv6_enabled() {
+        Some(true) if args.ignore_ipv6_enabled => (CheckStatus::Ok, "enabled, ignored".to_string()),
+        Some(true) => (
+            CheckStatus::Fail,
+            "enabled, node might not start up properly".to_string(),
+        ),
+        Some(false) => (CheckStatus::Ok, "disabled".to_string()),
+        None => (CheckStatus::Warn, "unable to determine".to_string()),
+    }
+}
+
+fn check_sysctls_recommended(_args: &Cli) -> Outcome {
+    let in_container = in_container();
+    let deviating: Vec<String> = check_sysctls()
+        .into_iter()
+        .filter(|report| report.state == SysctlState::Deviates)
+        .map(|report| {
+            let current = report.current.unwrap_or_default();
+            let host = if in_container && report.scope == SysctlScope::Host {
+                ", set on host"
+            } else {
+                ""
+            };
+            format!(
+                "{}={current} (recommended {}{host})",
+                report.key, report.recommended
+            )
+        })
+        .collect();
+
+    if deviating.is_empty() {
+        (CheckStatus::Ok, "within recommended ranges".to_string())
+    } else {
+        (CheckStatus::Warn, deviating.join(", "))
+    }
+}
+
+/// Chains with visor setup get hl-visor installed into the visor directory on startup, others use the one in PATH
+fn has_visor_setup(args: &Cli) -> bool {
+    args.network.as_ref().is_some_and(|network| {
+        args.chain_configs().is_ok_and(|configs| {
+            configs
+                .get(network)
+                .is_some_and(|config| config.visor.is_some())
+        })
+    })
+}
+
+fn check_hl_visor(args: &Cli) -> Outcome {
+    let installed = has_visor_setup(args);
+    let program = match (installed, args.visor_directory()) {
+        (true, Ok(directory)) => directory.join("hl-visor"),
+        _ => PathBuf::from("hl-visor"),
+    };
+
+    match which::which(&program) {
+        Ok(path) => (CheckStatus::Ok, path.display().to_string()),
+        Err(_) if installed => (
+            CheckStatus::Warn,
+            format!("{} not installed yet", program.display()),
+        ),
+        Err(err) => (CheckStatus::Fail, err.to_string()),
+    }
+}
+
+fn check_network(args: &Cli) -> Outcome {
+    match args.network() {
+        Ok(network) => (CheckStatus::Ok, network.to_string()),
+        Err(err) => (CheckStatus::Fail, format!("{err:#}")),
+    }
+}
+
+fn check_visor_chain(args: &Cli) -> Outcome {
+    let Ok(network) = args.network() else {
+        return (CheckStatus::Warn, "network unknown".to_string());
+    };
+
+    match args.visor_chain(&network) {
+        Some(chain) if chain == network => (CheckStatus::Ok, chain.to_string()),
+        Some(chain) => (
+            CheckStatus::Fail,
+            format!(
+                "visor.json is for {}, network is {}",
+                chain.to_string(),
+                network.to_string()
+            ),
+        ),
+        None => (CheckStatus::Warn, "visor.json not found".to_string()),
+    }
+}
+
+fn check_data_directory(args: &Cli) -> Outcome {
+    match args.data_directory() {
+        Ok(path) => check_writable(&path),
+        Err(err) => (CheckStatus::Fail, format!("{err:#}")),
+    }
+}
+
+fn check_visor_directory(args: &Cli) -> Outcome {
+    // hl-visor in PATH keeps visor.json & downloaded hl-node next to itself
+    if !has_visor_setup(args) {
+        return match which::which("hl-visor") {
+            Ok(path) => match path.parent() {
+                Some(directory) => check_writable(directory),
+                None => (
+                    CheckStatus::Fail,
+                    format!("failed to determine directory of {}", path.display()),
+                ),
+            },
+            Err(err) => (CheckStatus::Warn, format!("hl-visor not found: {err}")),
+        };
+    }
+
+    match args.visor_directory() {
+        Ok(path) => check_writable(&path),
+        Err(err) => (CheckStatus::Fail, format!("{err:#}")),
+    }
+}
+
+/// Creates a temporary file in the directory, or its closest existing parent when it does not exist yet
+fn check_writable(path: &Path) -> Outcome {
+    let Some(existing) = path.ancestors().find(|path| path.is_dir()) else {
+        return (
+            CheckStatus::Fail,
+            format!("{} does not exist", path.display()),
+        );
+    };
+
+    match NamedTempFile::new_in(existing) {
+        Ok(_) => (CheckStatus::Ok, format!("{} is writable", path.display())),
+        Err(err) => (
+            CheckStatus::Fail,
+            format!("{} is not writable: {err}", existing.display()),
+        ),
+    }
+}
+
+fn check_disk_space(args: &Cli) -> Outcome {
+    let path = match args.data_directory() {
+        Ok(path) => path,
+        Err(err) => return (CheckStatus::Fail, format!("{err:#}")),
+    };
+    let stat = match statvfs(&path) {
+        Ok(stat) => stat,
+        Err(err) => return (CheckStatus::Warn, format!("statvfs failed: {err}")),
+    };
+
+    let available = stat.blocks_available() as u64 * stat.fragment_size() as u64;
+    let detail = format!("{} GiB available on {}", available / GIB, path.display());
+    if available < DISK_SPACE_FAIL {
+        (CheckStatus::Fail, detail)
+    } else if available < DISK_SPACE_WARN {
+        (CheckStatus::Warn, detail)
+    } else {
+        (CheckStatus::Ok, detail)
+    }
+}
+
+fn check_inodes(args: &Cli) -> Outcome {
+    let path = match args.data_directory() {
+        Ok(path) => path,
+        Err(err) => return (CheckStatus::Fail, format!("{err:#}")),
+    };
+    let stat = match statvfs(&path) {
+        Ok(stat) => stat,
+        Err(err) => return (CheckStatus::Warn, format!("statvfs failed: {err}")),
+    };
+
+    // Some filesystems (btrfs, overlays of them) don't have a fixed inode count
+    if stat.files() == 0 {
+        return (CheckStatus::Ok, "no inode limit".to_string());
+    }
+    let available = stat.files_available() as u64;
+    let detail = format!("{available} of {} inodes available", stat.files());
+    if available < INODES_FAIL {
+        (CheckStatus::Fail, detail)
+    } else {
+        (CheckStatus::Ok, detail)
+    }
+}
+
+fn check_open_files(_args: &Cli) -> Outcome {
+    let (soft, hard) = match getrlimit(Resource::RLIMIT_NOFILE) {
+        Ok(limits) => limits,
+        Err(err) => return (CheckStatus::Warn, format!("getrlimit failed: {err}")),
+    };
+
+    let detail = format!("soft limit {soft}, hard limit {hard}");
+    if soft < OPEN_FILES_FAIL {
+        (CheckStatus::Fail, detail)
+    } else if soft < OPEN_FILES_WARN {
+        (CheckStatus::Warn, detail)
+    } else {
+        (CheckStatus::Ok, detail)
+    }
+}
+
+/// Container memory limit from cgroup v2 or v1, host memory when the container is not limited
+fn memory_limit() -> Option<(u64, &'static str)> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    let host = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemTotal:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse::<u64>()
+        .ok()?
+        * 1024;
+
+    // Unlimited is `max` in cgroup v2 & close to i64::MAX in cgroup v1
+    let cgroup = [
+        "/sys/fs/cgroup/memory.max",
+        "/sys/fs/cgroup/memory/memory.limit_in_bytes",
+    ]
+    .iter()
+    .find_map(|path| fs::read_to_string(path).ok()?.trim().parse::<u64>().ok());
+    match cgroup {
+        Some(limit) if limit < host => Some((limit, "cgroup")),
+        _ => Some((host, "host")),
+    }
+}
+
+fn check_memory(_args: &Cli) -> Outcome {
+    match memory_limit() {
+        Some((limit, source)) => {
+            let detail = format!("{} GiB ({source})", limit / GIB);
+            match limit < RECOMMENDED_MEMORY {
+                true => (
+                    CheckStatus::Warn,
+                    format!("{detail}, less than recommended"),
+                ),
+                false => (CheckStatus::Ok, detail),
+            }
+        }
+        None => (CheckStatus::Warn, "unable to determine".to_string()),
+    }
+}
+
+/// CPU quota from cgroup v2 `cpu.max` or v1 `cpu.cfs_quota_us`, None when unlimited
+fn cgroup_cpu_limit() -> Option<f64> {
+    if let Ok(content) = fs::read_to_string("/sys/fs/cgroup/cpu.max") {
+        return parse_cpu_max(&content);
+    }
+
+    let read = |path| fs::read_to_string(path).ok()?.trim().parse::<i64>().ok();
+    match (
+        read("/sys/fs/cgroup/cpu/cpu.cfs_quota_us"),
+        read("/sys/fs/cgroup/cpu/cpu.cfs_period_us"),
+    ) {
+        (Some(quota), Some(period)) if quota > 0 && period > 0 => {
+            Some(quota as f64 / period as f64)
+        }
+        _ => None,
+    }
+}
+
+/// `<quota> <period>` or `max <period>`
+fn parse_cpu_max(content: &str) -> Option<f64> {
+    let (quota, period) = content.trim().split_once(' ')?;
+    let quota: f64 = quota.parse().ok()?;
+    let period: f64 = period.parse().ok()?;
+    (period > 0.0).then(|| quota / period)
+}
+
+fn check_cpu(_args: &Cli) -> Outcome {
+    let host = std::thread::available_parallelism()
+        .map(|cpus| cpus.get() as f64)
+        .unwrap_or_default();
+    let (cpus, source) = match cgroup_cpu_limit() {
+        Some(limit) => (limit.min(host), "cgroup"),
+        None => (host, "host"),
+    };
+
+    let detail = format!("{cpus:.1} CPUs ({source})");
+    if cpus < RECOMMENDED_CPUS {
+        (
+            CheckStatus::Warn,
+            format!("{detail}, less than recommended"),
+        )
+    } else {
+        (CheckStatus::Ok, detail)
+    }
+}
+
+fn check_ports(_args: &Cli) -> Outcome {
+    let in_use: Vec<String> = NODE_PORTS
+        .iter()
+        .filter(|port| TcpListener::bind((Ipv4Addr::UNSPECIFIED, **port)).is_err())
+        .map(|port| port.to_string())
+        .collect();
+
+    match in_use.is_empty() {
+        true => (CheckStatus::Ok, "4000-4010, 3001 are free".to_string()),
+        false => (CheckStatus::Fail, format!("in use: {}", in_use.join(", "))),
+    }
+}
+
+/// hl-visor verifies downloaded hl-node binaries with gpg against the keyring of the current user
+fn check_gpg_keyring(_args: &Cli) -> Outcome {
+    let output = match Command::new("gpg")
+        .args(["--batch", "--list-keys", "--with-colons"])
+        .output()
+    {
+        Ok(output) => output,
+        Err(err) => return (CheckStatus::Fail, format!("failed to run gpg: {err}")),
+    };
+    if !output.status.success() {
+        return (
+            CheckStatus::Fail,
+            format!(
+                "gpg failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        );
+    }
+
+    if has_fingerprint(
+        &String::from_utf8_lossy(&output.stdout),
+        HYPERLIQUID_KEY_FINGERPRINT,
+    ) {
+        (
+            CheckStatus::Ok,
+            format!("Hyperliquid key {HYPERLIQUID_KEY_FINGERPRINT} present"),
+        )
+    } else {
+        (
+            CheckStatus::Fail,
+            format!(
+                "Hyperliquid key {HYPERLIQUID_KEY_FINGERPRINT} missing, import etc/hl-pubkey.asc"
+            ),
+        )
+    }
+}
+
+/// Whether `gpg --with-colons` key listing contains a key with given fingerprint
+fn has_fingerprint(listing: &str, fingerprint: &str) -> bool {
+    listing
+        .lines()
+        .filter_map(|line| line.strip_prefix("fpr:"))
+        .any(|fields| fields.split(':').nth(8) == Some(fingerprint))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_max() {
+        assert_eq!(parse_cpu_max("200000 100000\n"), Some(2.0));
+        assert_eq!(parse_cpu_max("max 100000\n"), None);
+        assert_eq!(parse_cpu_max(""), None);
+    }
+
+    #[test]
+    fn test_check_writable() -> eyre::Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        assert_eq!(check_writable(temp_dir.path()).0, CheckStatus::Ok);
+        assert_eq!(
+            check_writable(&temp_dir.path().join("hl-bootstrap/bin")).0,
+            CheckStatus::Ok
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_has_fingerprint() {
+        let listing = "pub:-:255:22:03254A9349F1820B:1736401048:::-:::scESC:::::ed25519:::0:\n\
+                       fpr:::::::::CF2C2EA3DC3E8F042A55FB6503254A9349F1820B:\n\
+                       uid:-::::1736401048::18A380A2F0D178708186DBEAAEDCB21A2C0B409D::Hyperliquid <notices@hyperfoundation.org>::::::::::0:\n\
+                       sub:-:255:18:A7DBE02923247B35:1736401048::::::e:::::cv25519::\n\
+                       fpr:::::::::21A9D47B2A20A55871AD4302A7DBE02923247B35:\n";
+        assert!(has_fingerprint(listing, HYPERLIQUID_KEY_FINGERPRINT));
+        assert!(!has_fingerprint(
+            "fpr:::::::::21A9D47B2A20A55871AD4302A7DBE02923247B35:\n",
+            HYPERLIQUID_KEY_FINGERPRINT
+        ));
+        assert!(!has_fingerprint("", HYPERLIQUID_KEY_FINGERPRINT));
+    }
+}
@@ -0,0 +1,273 @@
+use std::{fmt, net::Ipv4Addr};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tracing::{debug, info};
+
+use crate::{
+    hl_gossip_config::OverrideGossipConfig, peer_db::PeerDb, speedtest::SeedPeerMeasurement,
+};
+
+/// What changes in override_gossip_config.json when it gets replaced
+#[derive(Debug, Default, Serialize)]
+pub struct GossipConfigDiff {
+    /// root_node_ips which are new
+    pub added: Vec<PeerChange>,
+    /// root_node_ips which are dropped
+    pub removed: Vec<PeerChange>,
+    /// root_node_ips present in both
+    pub kept: Vec<PeerChange>,
+    /// Other top-level keys which differ
+    pub settings: Vec<SettingChange>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PeerChange {
+    pub ip: Ipv4Addr,
+    pub operator_name: Option<String>,
+    /// Seed peer source label(s) the peer came from
+    pub source: Option<String>,
+    /// Latest measurement for picked peers, median of recorded samples otherwise
+    pub latency_ms: Option<f64>,
+    pub last_failure_reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SettingChange {
+    pub key: String,
+    pub current: Value,
+    pub new: Value,
+}
+
+impl GossipConfigDiff {
+    /// Compares configurations, describing peers with this round's measurements & falling back to peer database
+    pub fn new(
+        current: Option<&OverrideGossipConfig>,
+        new: &OverrideGossipConfig,
+        seed_nodes: &[SeedPeerMeasurement],
+        peer_db: &PeerDb,
+    ) -> Self {
+        let current_ips: Vec<Ipv4Addr> = current
+            .iter()
+            .flat_map(|config| config.root_node_ips.iter().map(|node| node.ip))
+            .collect();
+        let new_ips: Vec<Ipv4Addr> = new.root_node_ips.iter().map(|node| node.ip).collect();
+
+        let describe = |ip: &Ipv4Addr| describe_peer(*ip, seed_nodes, peer_db);
+        let mut diff = Self {
+            added: new_ips
+                .iter()
+                .filter(|ip| !current_ips.contains(ip))
+                .map(describe)
+                .collect(),
+            removed: current_ips
+                .iter()
+                .filter(|ip| !new_ips.contains(ip))
+                .map(describe)
+                .collect(),
+            kept: new_ips
+                .iter()
+                .filter(|ip| current_ips.contains(ip))
+                .map(describe)
+                .collect(),
+            settings: Vec::new(),
+        };
+
+        // Everything is new without existing configuration, which peers already cover
+        if current.is_none() {
+            return diff;
+        }
+        let (current, new) = (to_map(current), to_map(Some(new)));
+        let mut keys: Vec<&String> = current.keys().chain(new.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            if key == "root_node_ips" || current.get(key) == new.get(key) {
+                continue;
+            }
+            diff.settings.push(SettingChange {
+                key: key.clone(),
+                current: current.get(key).cloned().unwrap_or_default(),
+                new: new.get(key).cloned().unwrap_or_default(),
+            });
+        }
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.settings.is_empty()
+    }
+
+    pub fn log(&self) {
+        if self.is_empty() {
+            info!(kept = self.kept.len(), "gossip config unchanged");
+            return;
+        }
+
+        info!(
+            added = self.added.len(),
+            removed = self.removed.len(),
+            kept = self.kept.len(),
+            "gossip config changed"
+        );
+        for (change, peers) in [("added", &self.added), ("removed", &self.removed)] {
+            for peer in peers {
+                info!(
+                    change,
+                    ip = ?peer.ip,
+                    operator_name = ?peer.operator_name,
+                    source = ?peer.source,
+                    latency_ms = ?peer.latency_ms,
+                    last_failure_reason = ?peer.last_failure_reason,
+                    "seed peer"
+                );
+            }
+        }
+        for peer in &self.kept {
+            debug!(ip = ?peer.ip, latency_ms = ?peer.latency_ms, "seed peer kept");
+        }
+        for setting in &self.settings {
+            info!(
+                key = setting.key,
+                current = %setting.current,
+                new = %setting.new,
+                "gossip config setting changed"
+            );
+        }
+    }
+}
+
+fn to_map(config: Option<&OverrideGossipConfig>) -> Map<String, Value> {
+    match config.map(serde_json::to_value) {
+        Some(Ok(Value::Object(map))) => map,
+        _ => Map::new(),
+    }
+}
+
+fn describe_peer(ip: Ipv4Addr, seed_nodes: &[SeedPeerMeasurement], peer_db: &PeerDb) -> PeerChange {
+    let record = peer_db.get(&ip);
+    let last_failure_reason = record.and_then(|record| record.last_failure_reason.clone());
+
+    match seed_nodes
+        .iter()
+        .find(|measurement| measurement.peer.ip == ip)
+    {
+        Some(measurement) => PeerChange {
+            ip,
+            operator_name: Some(measurement.peer.operator_name.clone()),
+            source: Some(measurement.peer.sources.join(",")),
+            latency_ms: Some(measurement.latency.as_secs_f64() * 1e3),
+            last_failure_reason: None,
+        },
+        None => PeerChange {
+            ip,
+            operator_name: None,
+            source: record
+                .filter(|record| !record.sources.is_empty())
+                .map(|record| record.sources.iter().cloned().collect::<Vec<_>>().join(",")),
+            latency_ms: record.and_then(|record| record.median_latency_ms()),
+            last_failure_reason,
+        },
+    }
+}
+
+impl fmt::Display for GossipConfigDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (marker, peers) in [("+", &self.added), ("-", &self.removed), (" ", &self.kept)] {
+            for peer in peers {
+                writeln!(
+                    f,
+                    "{marker} {:<15}  {:<24.24}  {:<20.20}  {:>8}  {}",
+                    peer.ip,
+                    peer.operator_name.as_deref().unwrap_or("-"),
+                    peer.source.as_deref().unwrap_or("-"),
+                    peer.latency_ms
+                        .map(|latency| format!("{latency:.2}ms"))
+                        .unwrap_or_else(|| "-".to_string()),
+                    peer.last_failure_reason.as_deref().unwrap_or(""),
+                )?;
+            }
+        }
+        for setting in &self.settings {
+            writeln!(
+                f,
+                "~ {}: {} -> {}",
+                setting.key, setting.current, setting.new
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        hl_gossip_config::{HyperliquidChain, HyperliquidSeedPeer, NodeIp},
+        speedtest::LatencyStats,
+    };
+
+    fn config(ips: &[[u8; 4]]) -> OverrideGossipConfig {
+        let mut config = OverrideGossipConfig::new(HyperliquidChain::Mainnet);
+        config.root_node_ips = ips
+            .iter()
+            .map(|ip| NodeIp {
+                ip: Ipv4Addr::from(*ip),
+            })
+            .collect();
+        config
+    }
+
+    #[test]
+    fn test_gossip_config_diff() {
+        let current = config(&[[1, 1, 1, 1], [2, 2, 2, 2]]);
+        let mut new = config(&[[2, 2, 2, 2], [3, 3, 3, 3]]);
+        new.try_new_peers = false;
+
+        let seed_nodes = vec![SeedPeerMeasurement {
+            peer: HyperliquidSeedPeer {
+                operator_name: "Carol".to_string(),
+                sources: vec!["builtin".to_string()],
+                ip: Ipv4Addr::new(3, 3, 3, 3),
+            },
+            latency: Duration::from_millis(12),
+            stats: LatencyStats::default(),
+            score: Duration::from_millis(12),
+        }];
+        let mut peer_db = PeerDb::default();
+        peer_db.record_failure(
+            &HyperliquidSeedPeer {
+                operator_name: "Alice".to_string(),
+                sources: vec!["fleet".to_string()],
+                ip: Ipv4Addr::new(1, 1, 1, 1),
+            },
+            "timed out",
+        );
+
+        let diff = GossipConfigDiff::new(Some(&current), &new, &seed_nodes, &peer_db);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].source.as_deref(), Some("builtin"));
+        assert_eq!(diff.added[0].latency_ms, Some(12.0));
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].source.as_deref(), Some("fleet"));
+        assert_eq!(
+            diff.removed[0].last_failure_reason.as_deref(),
+            Some("timed out")
+        );
+        assert_eq!(diff.kept[0].ip, Ipv4Addr::new(2, 2, 2, 2));
+        assert_eq!(diff.settings.len(), 1);
+        assert_eq!(diff.settings[0].key, "try_new_peers");
+
+        assert!(GossipConfigDiff::new(Some(&current), &current, &[], &peer_db).is_empty());
+        assert_eq!(
+            GossipConfigDiff::new(None, &current, &[], &peer_db)
+                .added
+                .len(),
+            2
+        );
+    }
+}
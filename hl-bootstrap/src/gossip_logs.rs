@@ -0,0 +1,262 @@
+use std::{
+    collections::HashMap,
+    fs,
+    net::Ipv4Addr,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use serde_json::Value;
+use tracing::{debug, trace, warn};
+
+use crate::peer_filter::is_bogon;
+
+/// How much of an unrecognized line is logged, enough to tell what format hl-node switched to
+const UNRECOGNIZED_SAMPLE_LEN: usize = 200;
+
+/// Events of gossip_connections records meaning the connection was established, other events (disconnects, failed
+/// attempts) don't prove anything about the peer
+const ESTABLISHED_EVENTS: &[&str] = &["connected", "verified"];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LearnedPeer {
+    pub ip: Ipv4Addr,
+    /// How many log lines reported an established connection with the peer
+    pub observations: u32,
+    /// Unix timestamp in seconds of the newest log file mentioning the peer
+    pub last_seen: u64,
+}
+
+/// Finds peers hl-node has actually been connected to, based on
+/// ${base}/hl/data/node_logs/gossip_connections/hourly/**/* files modified within `max_age`.
+/// Most observed peers come first.
+pub fn learn_gossip_peers<P: AsRef<Path>>(base_path: P, max_age: Duration) -> Vec<LearnedPeer> {
+    let logs_path = base_path
+        .as_ref()
+        .join("hl/data/node_logs/gossip_connections/hourly");
+    let now = SystemTime::now();
+
+    let mut peers: HashMap<Ipv4Addr, LearnedPeer> = HashMap::new();
+    let (mut files, mut records, mut unrecognized) = (0_usize, 0_usize, 0_usize);
+    collect_log_files(&logs_path, &mut |path, modified| {
+        if now.duration_since(modified).unwrap_or_default() > max_age {
+            return;
+        }
+
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) => {
+                warn!(?err, ?path, "failed to read gossip connections log");
+                return;
+            }
+        };
+        files += 1;
+
+        let last_seen = modified
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let (mut file_records, mut file_unrecognized, mut sample) = (0_usize, 0_usize, None);
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            let Some(record) = parse_connection_record(line) else {
+                file_unrecognized += 1;
+                sample.get_or_insert(line);
+                continue;
+            };
+            file_records += 1;
+            let Some(ip) = record.established_peer() else {
+                continue;
+            };
+
+            let peer = peers.entry(ip).or_insert(LearnedPeer {
+                ip,
+                observations: 0,
+                last_seen,
+            });
+            peer.observations += 1;
+            peer.last_seen = peer.last_seen.max(last_seen);
+        }
+
+        // Format is not documented, so make it visible once per file when hl-node changes it instead of silently
+        // learning nothing
+        if let Some(sample) = sample {
+            let sample: String = sample.chars().take(UNRECOGNIZED_SAMPLE_LEN).collect();
+            if file_records == 0 {
+                warn!(
+                    ?path,
+                    unrecognized = file_unrecognized,
+                    sample,
+                    "gossip connections log has no lines in expected format"
+                );
+            } else {
+                debug!(
+                    ?path,
+                    unrecognized = file_unrecognized,
+                    records = file_records,
+                    sample,
+                    "gossip connections log has some lines in unexpected format"
+                );
+            }
+        }
+        records += file_records;
+        unrecognized += file_unrecognized;
+    });
+
+    let mut peers: Vec<_> = peers.into_values().collect();
+    peers.sort_by(|a, b| {
+        b.observations
+            .cmp(&a.observations)
+            .then(b.last_seen.cmp(&a.last_seen))
+    });
+    debug!(
+        ?logs_path,
+        files,
+        records,
+        unrecognized,
+        peers = peers.len(),
+        "learned peers from gossip connections logs"
+    );
+
+    peers
+}
+
+fn collect_log_files(current_path: &Path, visit: &mut impl FnMut(&Path, SystemTime)) {
+    let entries = match fs::read_dir(current_path) {
+        Ok(entries) => entries,
+        Err(err) => {
+            trace!(?err, ?current_path, "failed to read directory");
+            return;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+
+        if metadata.is_dir() {
+            collect_log_files(&path, visit);
+        } else if metadata.is_file()
+            && let Ok(modified) = metadata.modified()
+        {
+            visit(&path, modified);
+        }
+    }
+}
+
+/// gossip_connections record, a JSON line of `[<timestamp>, {<event>: <details>}]`
+#[derive(Debug, PartialEq)]
+struct ConnectionRecord {
+    event: String,
+    /// Peer address from the `ip` detail, with or without port
+    ip: Option<Ipv4Addr>,
+}
+
+impl ConnectionRecord {
+    /// Peer of an established connection, unless it's a private/reserved address
+    fn established_peer(&self) -> Option<Ipv4Addr> {
+        let established = ESTABLISHED_EVENTS
+            .iter()
+            .any(|event| self.event.eq_ignore_ascii_case(event));
+
+        self.ip.filter(|ip| established && !is_bogon(ip))
+    }
+}
+
+fn parse_connection_record(line: &str) -> Option<ConnectionRecord> {
+    let Ok(Value::Array(record)) = serde_json::from_str(line) else {
+        return None;
+    };
+    let [Value::String(_timestamp), Value::Object(event)] = record.as_slice() else {
+        return None;
+    };
+    let mut event = event.iter();
+    let (Some((name, details)), None) = (event.next(), event.next()) else {
+        return None;
+    };
+
+    Some(ConnectionRecord {
+        event: name.clone(),
+        ip: details.get("ip").and_then(Value::as_str).and_then(parse_ip),
+    })
+}
+
+fn parse_ip(value: &str) -> Option<Ipv4Addr> {
+    let host = match value.rsplit_once(':') {
+        Some((host, port)) if port.parse::<u16>().is_ok() => host,
+        _ => value,
+    };
+
+    host.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    // Synthetic records in the `[<timestamp>, {<event>: <details>}]` shape, not captured from a running hl-node. Replace
+    // with real lines (logged as `sample` when a file has none in this shape) once available
+    const GOSSIP_CONNECTIONS_LOG: &str = r#"["2025-07-01T12:00:01.112233445",{"connected":{"ip":"1.2.3.4:4001","is_outbound":true}}]
+["2025-07-01T12:00:01.512233445",{"connected":{"ip":"5.6.7.8","is_outbound":false}}]
+["2025-07-01T12:00:02.012233445",{"connection_failed":{"ip":"9.9.9.9:4001","err":"Connection refused (os error 111)"}}]
+["2025-07-01T12:00:03.012233445",{"disconnected":{"ip":"1.2.3.4:4001"}}]
+["2025-07-01T12:00:04.012233445",{"connected":{"ip":"10.0.0.1:4001","is_outbound":true}}]
+["2025-07-01T12:05:00.000000000",{"connected":{"ip":"1.2.3.4:4001","is_outbound":true}}]
+"#;
+
+    #[test]
+    fn test_parse_connection_record() {
+        let records: Vec<_> = GOSSIP_CONNECTIONS_LOG
+            .lines()
+            .map(|line| parse_connection_record(line).unwrap())
+            .collect();
+        assert_eq!(
+            records[0],
+            ConnectionRecord {
+                event: "connected".to_string(),
+                ip: Some(Ipv4Addr::new(1, 2, 3, 4)),
+            }
+        );
+        let established: Vec<_> = records
+            .iter()
+            .filter_map(ConnectionRecord::established_peer)
+            .collect();
+        assert_eq!(
+            established,
+            [[1, 2, 3, 4], [5, 6, 7, 8], [1, 2, 3, 4]].map(Ipv4Addr::from)
+        );
+
+        // Plain text & other shapes mentioning addresses are not records
+        assert!(parse_connection_record("2025-07-01T12:00:00 connected to 5.6.7.8:4002").is_none());
+        assert!(parse_connection_record(r#"{"connected":{"ip":"5.6.7.8"}}"#).is_none());
+        assert!(parse_connection_record(r#"["2025-07-01", ["connected", "5.6.7.8"]]"#).is_none());
+    }
+
+    #[test]
+    fn test_learn_gossip_peers() -> eyre::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let logs = temp_dir
+            .path()
+            .join("hl/data/node_logs/gossip_connections/hourly/20250701");
+        fs::create_dir_all(&logs)?;
+        fs::write(logs.join("12"), GOSSIP_CONNECTIONS_LOG)?;
+        fs::write(logs.join("13"), "not a record 9.9.9.9:4001\n")?;
+
+        let peers = learn_gossip_peers(temp_dir.path(), Duration::from_secs(3600));
+        let ips: Vec<_> = peers
+            .iter()
+            .map(|peer| (peer.ip, peer.observations))
+            .collect();
+        assert_eq!(
+            ips,
+            vec![
+                (Ipv4Addr::new(1, 2, 3, 4), 2),
+                (Ipv4Addr::new(5, 6, 7, 8), 1)
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,283 @@
+use std::{collections::HashSet, fmt};
+
+use eyre::bail;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::warn;
+
+use crate::{
+    hl_gossip_config::{HyperliquidChain, OverrideGossipConfig, is_usable_reserved_peer_ip},
+    peer_filter::is_bogon,
+};
+
+/// Keys hl-node reads from override_gossip_config.json
+const KNOWN_KEYS: &[&str] = &[
+    "root_node_ips",
+    "try_new_peers",
+    "chain",
+    "n_gossip_peers",
+    "reserved_peer_ips",
+];
+
+/// Allowed n_gossip_peers range, see https://github.com/hyperliquid-dex/node/blob/main/README_misc.md#additional-configuration
+const N_GOSSIP_PEERS_RANGE: std::ops::RangeInclusive<u16> = 1..=100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Probably unintended, but hl-node works with it
+    Warning,
+    /// hl-node rejects the configuration or can't sync with it
+    Error,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub key: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{severity}: {}: {}", self.key, self.message)
+    }
+}
+
+/// Checks configuration against hl-node constraints. `visor_chain` is the chain from visor.json, when known.
+pub fn validate_gossip_config(
+    config: &OverrideGossipConfig,
+    visor_chain: Option<&HyperliquidChain>,
+) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let mut issue = |severity, key: &str, message: String| {
+        issues.push(ValidationIssue {
+            severity,
+            key: key.to_string(),
+            message,
+        })
+    };
+
+    if config.root_node_ips.is_empty() && !config.try_new_peers {
+        issue(
+            Severity::Error,
+            "root_node_ips",
+            "no root nodes while try_new_peers is disabled, node has nobody to gossip with"
+                .to_string(),
+        );
+    }
+
+    let mut seen = HashSet::new();
+    for node in &config.root_node_ips {
+        if !seen.insert(node.ip) {
+            issue(
+                Severity::Warning,
+                "root_node_ips",
+                format!("{} is listed more than once", node.ip),
+            );
+        }
+
+        if node.ip.is_unspecified() || node.ip.is_broadcast() || node.ip.is_multicast() {
+            issue(
+                Severity::Error,
+                "root_node_ips",
+                format!("{} is not a valid peer address", node.ip),
+            );
+        } else if is_bogon(&node.ip) && !config.reserved_peer_ips.contains(&node.ip) {
+            // Private addresses are expected for sentries, which are reserved peers as well
+            issue(
+                Severity::Warning,
+                "root_node_ips",
+                format!("{} is not publicly routable", node.ip),
+            );
+        }
+    }
+
+    let mut seen = HashSet::new();
+    for ip in &config.reserved_peer_ips {
+        if !seen.insert(*ip) {
+            issue(
+                Severity::Warning,
+                "reserved_peer_ips",
+                format!("{ip} is listed more than once"),
+            );
+        }
+
+        if !is_usable_reserved_peer_ip(ip) {
+            issue(
+                Severity::Error,
+                "reserved_peer_ips",
+                format!("{ip} can't be used as a reserved peer"),
+            );
+        }
+    }
+
+    if let Some(n_gossip_peers) = config.n_gossip_peers
+        && !N_GOSSIP_PEERS_RANGE.contains(&n_gossip_peers)
+    {
+        issue(
+            Severity::Error,
+            "n_gossip_peers",
+            format!(
+                "{n_gossip_peers} is outside of allowed range [{}, {}]",
+                N_GOSSIP_PEERS_RANGE.start(),
+                N_GOSSIP_PEERS_RANGE.end()
+            ),
+        );
+    }
+
+    if let Some(visor_chain) = visor_chain
+        && *visor_chain != config.chain
+    {
+        issue(
+            Severity::Error,
+            "chain",
+            format!(
+                "{} does not match chain {} in visor.json",
+                config.chain.to_string(),
+                visor_chain.to_string()
+            ),
+        );
+    }
+
+    if let Value::Object(unknown) = &config.unknown {
+        for key in unknown.keys() {
+            let message = match closest_known_key(key) {
+                Some(known) => format!("unknown key, did you mean '{known}'?"),
+                None => "unknown key, hl-node might ignore it".to_string(),
+            };
+            issue(Severity::Warning, key, message);
+        }
+    }
+
+    issues.sort_by(|a, b| b.severity.cmp(&a.severity));
+    issues
+}
+
+/// Logs warnings & fails on errors
+pub fn check_gossip_config(
+    config: &OverrideGossipConfig,
+    visor_chain: Option<&HyperliquidChain>,
+) -> eyre::Result<()> {
+    let issues = validate_gossip_config(config, visor_chain);
+    for issue in &issues {
+        warn!(
+            severity = ?issue.severity,
+            key = issue.key,
+            message = issue.message,
+            "gossip config issue"
+        );
+    }
+
+    let errors = issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        bail!("gossip config has {errors} error(s), hl-node would not work with it");
+    }
+
+    Ok(())
+}
+
+fn closest_known_key(key: &str) -> Option<&'static str> {
+    let key = key.to_lowercase();
+    KNOWN_KEYS
+        .iter()
+        .map(|known| (*known, levenshtein(&key, known)))
+        .filter(|(known, distance)| *distance <= (known.len() / 3).max(2))
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(known, _)| known)
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issues(config: &str, visor_chain: Option<HyperliquidChain>) -> Vec<(Severity, String)> {
+        let config: OverrideGossipConfig = serde_json::from_str(config).unwrap();
+        validate_gossip_config(&config, visor_chain.as_ref())
+            .into_iter()
+            .map(|issue| (issue.severity, issue.key))
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_gossip_config() {
+        assert!(
+            issues(
+                r#"{"root_node_ips": [{"Ip": "1.2.3.4"}], "try_new_peers": true, "chain": "Mainnet"}"#,
+                Some(HyperliquidChain::Mainnet)
+            )
+            .is_empty()
+        );
+
+        assert_eq!(
+            issues(
+                r#"{"root_node_ips": [{"Ip": "1.2.3.4"}, {"Ip": "1.2.3.4"}, {"Ip": "10.0.0.1"}, {"Ip": "0.0.0.0"}],
+                    "try_new_peers": true, "chain": "Mainnet", "n_gossip_peers": 0, "n_gosip_peers": 5, "foo": 1}"#,
+                Some(HyperliquidChain::Testnet)
+            ),
+            vec![
+                (Severity::Error, "root_node_ips".to_string()),
+                (Severity::Error, "n_gossip_peers".to_string()),
+                (Severity::Error, "chain".to_string()),
+                (Severity::Warning, "root_node_ips".to_string()),
+                (Severity::Warning, "root_node_ips".to_string()),
+                (Severity::Warning, "foo".to_string()),
+                (Severity::Warning, "n_gosip_peers".to_string()),
+            ]
+        );
+
+        assert!(
+            issues(
+                r#"{"root_node_ips": [{"Ip": "10.0.0.1"}], "try_new_peers": false, "chain": "Mainnet",
+                    "reserved_peer_ips": ["10.0.0.1"]}"#,
+                None
+            )
+            .is_empty()
+        );
+        assert_eq!(
+            issues(
+                r#"{"try_new_peers": true, "chain": "Mainnet",
+                    "reserved_peer_ips": ["10.0.0.1", "127.0.0.1", "10.0.0.1"]}"#,
+                None
+            ),
+            vec![
+                (Severity::Error, "reserved_peer_ips".to_string()),
+                (Severity::Warning, "reserved_peer_ips".to_string()),
+            ]
+        );
+        assert_eq!(
+            issues(r#"{"try_new_peers": false, "chain": "Mainnet"}"#, None),
+            vec![(Severity::Error, "root_node_ips".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_closest_known_key() {
+        assert_eq!(closest_known_key("n_gosip_peers"), Some("n_gossip_peers"));
+        assert_eq!(closest_known_key("Try_New_Peer"), Some("try_new_peers"));
+        assert_eq!(closest_known_key("something_else"), None);
+    }
+}
@@ -0,0 +1,25 @@
+use std::path::Path;
+
+use eyre::{Context, bail};
+use tokio::process::Command;
+
+/// Verifies detached signature using current user's gpg keyring
+pub async fn verify_detached_signature(signature: &Path, data: &Path) -> eyre::Result<()> {
+    let output = Command::new("gpg")
+        .arg("--batch")
+        .arg("--verify")
+        .arg(signature)
+        .arg(data)
+        .output()
+        .await
+        .wrap_err("failed to run gpg")?;
+
+    if !output.status.success() {
+        bail!(
+            "gpg signature verification failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(())
+}
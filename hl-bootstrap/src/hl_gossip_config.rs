@@ -1,10 +1,20 @@
-use std::{collections::HashSet, net::Ipv4Addr, str::FromStr};
-
-use eyre::{Context, ContextCompat, bail};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::ErrorKind,
+    net::Ipv4Addr,
+    path::Path,
+    str::FromStr,
+};
+
+use clap::ValueEnum;
+use eyre::{Context, bail};
 use serde::{Deserialize, Serialize};
-use serde_json::{Value, json};
+use serde_json::Value;
 use tracing::{debug, warn};
 
+use crate::{peer_filter::PeerFilter, seed_cache::SeedCache, seed_source::SeedPeerSource};
+
 structstruck::strike! {
     #[structstruck::each[derive(Clone, Debug, Deserialize, Serialize)]]
     pub struct OverrideGossipConfig {
@@ -16,15 +26,20 @@ structstruck::strike! {
         #[serde(default)]
         pub try_new_peers: bool,
         pub chain: pub enum HyperliquidChain {
-            #![derive(Copy)]
+            #![derive(PartialEq, Eq, Hash)]
+            // Plain chain name string, so custom chains round-trip the same way as builtin ones
+            #![serde(into = "String", try_from = "String")]
 
-            #[serde(rename = "Mainnet")]
             Mainnet,
-            #[serde(rename = "Testnet")]
             Testnet,
+            /// Local devnet or private test chain, configured via chain config
+            Custom(String),
         },
         #[serde(skip_serializing_if = "Option::is_none")]
         pub n_gossip_peers: Option<u16>,
+        /// Peers which are always accepted, e.g. private nodes behind a sentry
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub reserved_peer_ips: Vec<Ipv4Addr>,
         #[serde(flatten, default)]
         pub unknown: Value,
     }
@@ -37,9 +52,164 @@ impl OverrideGossipConfig {
             try_new_peers: true,
             chain,
             n_gossip_peers: None,
+            reserved_peer_ips: Default::default(),
             unknown: Default::default(),
         }
     }
+
+    /// Merges freshly discovered configuration into this (existing) configuration.
+    /// Chain is always taken from the discovered configuration, unknown keys are always kept.
+    pub fn merge(mut self, discovered: Self, policies: &MergePolicies) -> Self {
+        if self.chain != discovered.chain {
+            warn!(
+                existing = ?self.chain,
+                discovered = ?discovered.chain,
+                "existing gossip config is for a different chain, replacing it"
+            );
+            self.chain = discovered.chain;
+        }
+
+        self.root_node_ips = match policies.root_node_ips {
+            MergePolicy::Keep if !self.root_node_ips.is_empty() => self.root_node_ips,
+            MergePolicy::Keep | MergePolicy::Override => discovered.root_node_ips,
+            MergePolicy::Union => {
+                let mut seen = HashSet::new();
+                discovered
+                    .root_node_ips
+                    .into_iter()
+                    .chain(self.root_node_ips)
+                    .filter(|node| seen.insert(node.ip))
+                    .collect()
+            }
+        };
+
+        self.try_new_peers = match policies.try_new_peers {
+            MergePolicy::Keep => self.try_new_peers,
+            MergePolicy::Override => discovered.try_new_peers,
+            MergePolicy::Union => self.try_new_peers || discovered.try_new_peers,
+        };
+
+        self.n_gossip_peers = match policies.n_gossip_peers {
+            MergePolicy::Keep => self.n_gossip_peers.or(discovered.n_gossip_peers),
+            MergePolicy::Override => discovered.n_gossip_peers,
+            MergePolicy::Union => self.n_gossip_peers.max(discovered.n_gossip_peers),
+        };
+
+        self.reserved_peer_ips = match policies.reserved_peer_ips {
+            MergePolicy::Keep if !self.reserved_peer_ips.is_empty() => self.reserved_peer_ips,
+            MergePolicy::Keep | MergePolicy::Override => discovered.reserved_peer_ips,
+            MergePolicy::Union => {
+                let mut seen = HashSet::new();
+                self.reserved_peer_ips
+                    .into_iter()
+                    .chain(discovered.reserved_peer_ips)
+                    .filter(|ip| seen.insert(*ip))
+                    .collect()
+            }
+        };
+
+        self
+    }
+}
+
+/// Reserved peers have to be addresses other nodes can actually connect to
+pub fn is_usable_reserved_peer_ip(ip: &Ipv4Addr) -> bool {
+    !(ip.is_unspecified() || ip.is_broadcast() || ip.is_multicast() || ip.is_loopback())
+}
+
+pub fn parse_reserved_peer_ip(value: &str) -> eyre::Result<Ipv4Addr> {
+    let ip: Ipv4Addr = value
+        .trim()
+        .parse()
+        .wrap_err_with(|| format!("invalid reserved peer IP '{value}'"))?;
+    if !is_usable_reserved_peer_ip(&ip) {
+        bail!("{ip} can't be used as a reserved peer");
+    }
+
+    Ok(ip)
+}
+
+/// Node's place in a sentry topology
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum NodeRole {
+    /// Gossips with discovered seed peers
+    #[default]
+    Standalone,
+    /// Gossips with discovered seed peers, always accepting the private nodes given as reserved peers
+    Sentry,
+    /// Gossips only with the sentry nodes given as reserved peers
+    Private,
+}
+
+/// How a field in existing override_gossip_config.json is combined with the discovered value
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum MergePolicy {
+    /// Keep the existing value, use discovered value only when existing is unset (or empty)
+    Keep,
+    /// Always use the discovered value
+    Override,
+    /// Combine both: union for lists, logical or for flags, maximum for numbers
+    Union,
+}
+
+#[derive(Clone, Debug)]
+pub struct MergePolicies {
+    pub root_node_ips: MergePolicy,
+    pub try_new_peers: MergePolicy,
+    pub n_gossip_peers: MergePolicy,
+    pub reserved_peer_ips: MergePolicy,
+}
+
+/// Reads existing override_gossip_config.json, if there is one, leaving unparseable files in place
+pub fn read_override_gossip_config<P: AsRef<Path>>(
+    path: P,
+) -> eyre::Result<Option<OverrideGossipConfig>> {
+    let path = path.as_ref();
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => {
+            return Err(err).wrap_err_with(|| format!("failed to open gossip config at {path:?}"));
+        }
+    };
+
+    serde_json::from_reader(file)
+        .map(Some)
+        .wrap_err_with(|| format!("failed to parse gossip config at {path:?}"))
+}
+
+/// Loads existing override_gossip_config.json, if there is one.
+/// Unparseable files are moved aside as `<path>.invalid` so manual edits aren't lost.
+pub fn load_override_gossip_config<P: AsRef<Path>>(
+    path: P,
+) -> eyre::Result<Option<OverrideGossipConfig>> {
+    let path = path.as_ref();
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => {
+            return Err(err).wrap_err_with(|| format!("failed to open gossip config at {path:?}"));
+        }
+    };
+
+    match serde_json::from_reader(file) {
+        Ok(config) => Ok(Some(config)),
+        Err(err) => {
+            let mut invalid_path = path.as_os_str().to_owned();
+            invalid_path.push(".invalid");
+
+            warn!(
+                ?err,
+                ?path,
+                ?invalid_path,
+                "failed to parse existing gossip config, moving it aside"
+            );
+            fs::rename(path, &invalid_path)
+                .wrap_err_with(|| format!("failed to move invalid gossip config at {path:?}"))?;
+
+            Ok(None)
+        }
+    }
 }
 
 impl FromStr for HyperliquidChain {
@@ -49,17 +219,38 @@ impl FromStr for HyperliquidChain {
         Ok(match s.to_lowercase().as_str() {
             "mainnet" => Self::Mainnet,
             "testnet" => Self::Testnet,
-            chain => bail!("unsupported chain '{chain}'"),
+            _ if !s.is_empty()
+                && s.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+            {
+                Self::Custom(s.to_string())
+            }
+            _ => bail!("unsupported chain '{s}'"),
         })
     }
 }
 
+impl TryFrom<String> for HyperliquidChain {
+    type Error = eyre::ErrReport;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<HyperliquidChain> for String {
+    fn from(value: HyperliquidChain) -> Self {
+        value.to_string()
+    }
+}
+
 #[allow(clippy::to_string_trait_impl)]
 impl ToString for HyperliquidChain {
     fn to_string(&self) -> String {
         match self {
             Self::Mainnet => "Mainnet",
             Self::Testnet => "Testnet",
+            Self::Custom(name) => name,
         }
         .to_string()
     }
@@ -67,8 +258,9 @@ impl ToString for HyperliquidChain {
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct HyperliquidSeedPeer {
-    #[allow(dead_code)] // Keeping due to its value in logs
     pub operator_name: String,
+    /// Labels of the seed peer sources reporting this peer
+    pub sources: Vec<String>,
     pub ip: Ipv4Addr,
 }
 
@@ -78,212 +270,98 @@ impl From<HyperliquidSeedPeer> for NodeIp {
     }
 }
 
+/// Fetches seed peers from all given sources, merging the results. With `quorum` above 1, only IPs reported by at
+/// least that many distinct sources are kept.
 pub async fn fetch_hyperliquid_seed_peers(
-    chain: HyperliquidChain,
-    ignored_peers: &HashSet<Ipv4Addr>,
+    sources: &[SeedPeerSource],
+    peer_filter: &PeerFilter,
+    cache: &mut SeedCache,
+    quorum: usize,
 ) -> eyre::Result<Vec<HyperliquidSeedPeer>> {
-    match chain {
-        HyperliquidChain::Mainnet => {
-            let mut all_peers = HashSet::new();
-
-            match fetch_mainnet_seed_peers_api(ignored_peers).await {
-                Ok(peers) => all_peers.extend(peers),
-                Err(err) => warn!(
-                    ?err,
-                    "failed to get usable mainnet peers from Hyperliquid API"
-                ),
-            }
-
-            match fetch_mainnet_seed_peers_markdown_table(ignored_peers).await {
-                Ok(peers) => all_peers.extend(peers),
-                Err(err) => warn!(?err, "failed to get usable peers from markdown table"),
-            };
-
-            if all_peers.is_empty() {
-                bail!("No usable seed peers found");
+    let client = reqwest::Client::new();
+    let mut all_peers = Vec::new();
+
+    for source in sources {
+        match source.fetch(&client, peer_filter, cache).await {
+            Ok(peers) => {
+                debug!(
+                    source = source.label,
+                    count = peers.len(),
+                    "got seed peers from source"
+                );
+                all_peers.extend(peers);
             }
-
-            Ok(Vec::from_iter(all_peers))
+            Err(err) => warn!(
+                ?err,
+                source = source.label,
+                "failed to get usable seed peers from source"
+            ),
         }
-        HyperliquidChain::Testnet => fetch_testnet_seed_peers(ignored_peers).await,
     }
-}
 
-async fn fetch_mainnet_seed_peers_api(
-    ignored_peers: &HashSet<Ipv4Addr>,
-) -> eyre::Result<Vec<HyperliquidSeedPeer>> {
-    let peer_ips: Vec<Ipv4Addr> = reqwest::Client::new()
-        .post("https://api.hyperliquid.xyz/info")
-        .json(&json!({"type": "gossipRootIps"}))
-        .send()
-        .await
-        .wrap_err("failed to get mainnet seed nodes")?
-        .error_for_status()
-        .wrap_err("failed to get mainnet seed nodes")?
-        .json()
-        .await
-        .wrap_err("failed to parse mainnet seed nodes")?;
-
-    if peer_ips.is_empty() {
-        bail!("No seed peers were given from Hyperliquid API");
+    if all_peers.is_empty() {
+        bail!("No usable seed peers found");
     }
 
-    let mut seeds = Vec::new();
-    for ip in peer_ips {
-        if ignored_peers.contains(&ip) {
-            debug!(?ip, "skipping ignored seed node");
-            continue;
-        }
-
-        seeds.push(HyperliquidSeedPeer {
-            operator_name: "Hyperliquid API-provided IP".to_string(),
-            ip,
-        });
+    let peers = apply_quorum(merge_seed_peers(all_peers), quorum);
+    if peers.is_empty() {
+        bail!("No seed peers were reported by at least {quorum} sources");
     }
 
-    Ok(seeds)
+    Ok(peers)
 }
 
-async fn fetch_mainnet_seed_peers_markdown_table(
-    ignored_peers: &HashSet<Ipv4Addr>,
-) -> eyre::Result<Vec<HyperliquidSeedPeer>> {
-    // There is an API request to fetch mainnet non-validating seed node IPs since 2025-09-02, but it'll only give us
-    // JP IP addresses, which are usually unsuitable for syncing the node from EU.
-    // Keep Markdown table parsing code around for now.
-    let url = "https://github.com/hyperliquid-dex/node/raw/refs/heads/main/README.md";
-
-    // Fetch the README content
-    let response = reqwest::get(url).await?;
-    let content = response.text().await?;
-
-    let mut peers = Vec::new();
-
-    // Find the table section that contains the seed peers
-    // Look for the "Mainnet Non-Validator Seed Peers" section
-    let seed_peers_section = content
-        .split("## Mainnet Non-Validator Seed Peers")
-        .nth(1)
-        .wrap_err("could not find 'Mainnet Non-Validator Seed Peers' section from hyperliquid-dex/node README.md")?;
-
-    // Split by next section (starts with ##) to isolate just the peers table
-    let peers_content = seed_peers_section
-        .split("##")
-        .next()
-        .unwrap_or(seed_peers_section);
-
-    // Find the table by looking for lines that start and end with |
-    let lines: Vec<&str> = peers_content.lines().collect();
-    let mut in_table = false;
-    let mut header_found = false;
-
-    for line in lines {
-        let trimmed = line.trim();
-
-        // Skip empty lines
-        if trimmed.is_empty() {
-            continue;
-        }
-
-        // Check if this line looks like a markdown table row
-        if trimmed.starts_with('|') && trimmed.ends_with('|') {
-            // Skip separator lines (contains only |, -, and spaces)
-            if trimmed
-                .chars()
-                .all(|c| c == '|' || c == '-' || c.is_whitespace())
-            {
-                in_table = true;
-                continue;
-            }
-
-            // Parse the table cells
-            let cells: Vec<&str> = trimmed
-                .split('|')
-                .map(|cell| cell.trim())
-                .filter(|cell| !cell.is_empty())
-                .collect();
-
-            // Skip header row
-            if !header_found
-                && cells.len() >= 2
-                && (cells[0].to_lowercase().contains("operator")
-                    || cells[1].to_lowercase().contains("root")
-                    || cells[1].to_lowercase().contains("ip"))
-            {
-                header_found = true;
-                in_table = true;
-                continue;
-            }
-
-            // Parse data rows
-            if in_table && header_found && cells.len() >= 2 {
-                let operator_name = cells[0].to_string();
-                let ip_str = cells[1];
-
-                // Parse the IP address
-                match ip_str.parse::<Ipv4Addr>() {
-                    Ok(ip) => {
-                        if ignored_peers.contains(&ip) {
-                            debug!(operator_name, ?ip, "skipping ignored seed node");
-                            continue;
-                        }
-
-                        peers.push(HyperliquidSeedPeer { operator_name, ip });
-                    }
-                    Err(err) => {
-                        debug!(?err, ip_str, "failed to parse ip");
-                        continue;
+/// Dedups peers by IP, keeping the first operator name & all distinct sources reporting the IP
+pub fn merge_seed_peers(
+    peers: impl IntoIterator<Item = HyperliquidSeedPeer>,
+) -> Vec<HyperliquidSeedPeer> {
+    let mut merged: Vec<HyperliquidSeedPeer> = Vec::new();
+    let mut positions: HashMap<Ipv4Addr, usize> = HashMap::new();
+    for peer in peers {
+        match positions.get(&peer.ip) {
+            Some(&position) => {
+                let sources = &mut merged[position].sources;
+                for source in peer.sources {
+                    if !sources.contains(&source) {
+                        sources.push(source);
                     }
                 }
             }
-        } else if in_table {
-            // If we were in a table but this line doesn't look like a table row,
-            // we've probably reached the end of the table
-            break;
+            None => {
+                positions.insert(peer.ip, merged.len());
+                merged.push(peer);
+            }
         }
     }
 
-    if peers.is_empty() {
-        bail!("No valid seed peers found in markdown table");
-    }
-
-    Ok(peers)
+    merged
 }
 
-async fn fetch_testnet_seed_peers(
-    ignored_peers: &HashSet<Ipv4Addr>,
-) -> eyre::Result<Vec<HyperliquidSeedPeer>> {
-    // Imperator.co is generous
-    let url = "https://hyperliquid-testnet.imperator.co/peers.json";
-
-    let config: OverrideGossipConfig = reqwest::get(url)
-        .await
-        .wrap_err("failed to get testnet seed nodes")?
-        .error_for_status()?
-        .json()
-        .await
-        .wrap_err("failed to parse testnet override_gossip_config")?;
-
-    let operator_name = "Imperator.co";
-
-    let mut seeds = Vec::new();
-    for node in config.root_node_ips {
-        if ignored_peers.contains(&node.ip) {
-            debug!(operator_name, ip = ?node.ip, "skipping ignored seed node");
-            continue;
-        }
+/// Keeps peers reported by at least `quorum` distinct sources, so a single compromised source can't decide which
+/// peers the node connects to
+fn apply_quorum(peers: Vec<HyperliquidSeedPeer>, quorum: usize) -> Vec<HyperliquidSeedPeer> {
+    if quorum <= 1 {
+        return peers;
+    }
 
-        seeds.push(HyperliquidSeedPeer {
-            operator_name: operator_name.to_string(),
-            ip: node.ip,
-        });
+    let (kept, dropped): (Vec<_>, Vec<_>) = peers
+        .into_iter()
+        .partition(|peer| peer.sources.len() >= quorum);
+    if !dropped.is_empty() {
+        debug!(
+            quorum,
+            dropped = ?dropped.iter().map(|peer| peer.ip).collect::<Vec<_>>(),
+            "skipping seed peers without source quorum"
+        );
     }
 
-    Ok(seeds)
+    kept
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{peer_filter::ExtraSeedPeer, seed_source::builtin_seed_sources};
 
     #[test]
     fn test_parse_override_gossip_config() -> eyre::Result<()> {
@@ -300,6 +378,154 @@ mod tests {
         dbg!(&config);
         let serialized = serde_json::to_string_pretty(&config)?;
         println!("{serialized}");
+        assert_eq!(config.reserved_peer_ips, vec![Ipv4Addr::new(5, 6, 7, 8)]);
+        assert!(config.unknown.get("reserved_peer_ips").is_none());
+
+        // Unusable addresses are reported by validation instead, so the file is not discarded
+        let config: OverrideGossipConfig =
+            serde_json::from_str(r#"{"chain": "Mainnet", "reserved_peer_ips": ["0.0.0.0"]}"#)?;
+        assert_eq!(config.reserved_peer_ips, vec![Ipv4Addr::UNSPECIFIED]);
+        assert!(parse_reserved_peer_ip("0.0.0.0").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_chain() -> eyre::Result<()> {
+        assert_eq!(
+            "mainnet".parse::<HyperliquidChain>()?,
+            HyperliquidChain::Mainnet
+        );
+        assert_eq!(
+            "local-devnet".parse::<HyperliquidChain>()?,
+            HyperliquidChain::Custom("local-devnet".to_string())
+        );
+        assert!("".parse::<HyperliquidChain>().is_err());
+        assert!("Dev net".parse::<HyperliquidChain>().is_err());
+
+        let config = OverrideGossipConfig::new("local-devnet".parse()?);
+        let serialized = serde_json::to_string(&config)?;
+        assert!(serialized.contains(r#""chain":"local-devnet""#));
+        assert_eq!(
+            serde_json::from_str::<OverrideGossipConfig>(&serialized)?.chain,
+            config.chain
+        );
+        assert!(
+            serde_json::to_string(&OverrideGossipConfig::new(HyperliquidChain::Mainnet))?
+                .contains(r#""chain":"Mainnet""#)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_override_gossip_config() -> eyre::Result<()> {
+        let existing: OverrideGossipConfig = serde_json::from_str(
+            r#"
+            {
+                "root_node_ips": [{"Ip": "1.2.3.4"}],
+                "try_new_peers": false,
+                "chain": "Mainnet",
+                "n_gossip_peers": 20,
+                "reserved_peer_ips": ["5.6.7.8"]
+            }
+        "#,
+        )?;
+
+        let mut discovered = OverrideGossipConfig::new(HyperliquidChain::Mainnet);
+        discovered.root_node_ips = vec![
+            NodeIp {
+                ip: Ipv4Addr::new(9, 9, 9, 9),
+            },
+            NodeIp {
+                ip: Ipv4Addr::new(1, 2, 3, 4),
+            },
+        ];
+        discovered.n_gossip_peers = Some(10);
+
+        let policies = MergePolicies {
+            root_node_ips: MergePolicy::Override,
+            try_new_peers: MergePolicy::Keep,
+            n_gossip_peers: MergePolicy::Keep,
+            reserved_peer_ips: MergePolicy::Union,
+        };
+        let merged = existing.clone().merge(discovered.clone(), &policies);
+        assert_eq!(merged.root_node_ips.len(), 2);
+        assert!(!merged.try_new_peers);
+        assert_eq!(merged.n_gossip_peers, Some(20));
+        assert_eq!(merged.reserved_peer_ips, vec![Ipv4Addr::new(5, 6, 7, 8)]);
+
+        let policies = MergePolicies {
+            root_node_ips: MergePolicy::Union,
+            try_new_peers: MergePolicy::Union,
+            n_gossip_peers: MergePolicy::Override,
+            reserved_peer_ips: MergePolicy::Override,
+        };
+        let mut existing = existing;
+        existing.root_node_ips.push(NodeIp {
+            ip: Ipv4Addr::new(4, 3, 2, 1),
+        });
+        let merged = existing.merge(discovered, &policies);
+        let ips: Vec<_> = merged.root_node_ips.iter().map(|node| node.ip).collect();
+        assert_eq!(
+            ips,
+            vec![
+                Ipv4Addr::new(9, 9, 9, 9),
+                Ipv4Addr::new(1, 2, 3, 4),
+                Ipv4Addr::new(4, 3, 2, 1),
+            ]
+        );
+        assert!(merged.try_new_peers);
+        assert_eq!(merged.n_gossip_peers, Some(10));
+        assert!(merged.reserved_peer_ips.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_quorum() {
+        let peer = |source: &str, ip: [u8; 4]| HyperliquidSeedPeer {
+            operator_name: source.to_string(),
+            sources: vec![source.to_string()],
+            ip: Ipv4Addr::from(ip),
+        };
+        let peers = merge_seed_peers([
+            peer("a", [1, 1, 1, 1]),
+            peer("b", [1, 1, 1, 1]),
+            peer("a", [2, 2, 2, 2]),
+            peer("a", [3, 3, 3, 3]),
+            peer("a", [3, 3, 3, 3]),
+        ]);
+        assert_eq!(peers.len(), 3);
+        assert_eq!(peers[0].operator_name, "a");
+        assert_eq!(peers[0].sources, vec!["a", "b"]);
+        assert_eq!(peers[2].sources, vec!["a"]);
+
+        assert_eq!(apply_quorum(peers.clone(), 1).len(), 3);
+        let kept = apply_quorum(peers, 2);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].ip, Ipv4Addr::new(1, 1, 1, 1));
+    }
+
+    #[test]
+    fn test_merge_extra_seed_peers() -> eyre::Result<()> {
+        let fetched = vec![HyperliquidSeedPeer {
+            operator_name: "operator".to_string(),
+            sources: vec!["builtin".to_string()],
+            ip: Ipv4Addr::new(1, 2, 3, 4),
+        }];
+        let extra: Vec<ExtraSeedPeer> = vec!["1.2.3.4=mine".parse()?, "5.6.7.8".parse()?];
+        let peers = merge_seed_peers(
+            fetched
+                .into_iter()
+                .chain(extra.iter().map(HyperliquidSeedPeer::from)),
+        );
+
+        assert_eq!(peers.len(), 2);
+        assert_eq!(peers[0].operator_name, "operator");
+        assert_eq!(peers[0].sources, vec!["builtin", "args"]);
+        assert_eq!(peers[1].ip, Ipv4Addr::new(5, 6, 7, 8));
+        assert_eq!(peers[1].sources, vec!["args"]);
 
         Ok(())
     }
@@ -307,9 +533,11 @@ mod tests {
     // Requires network access
     #[tokio::test]
     async fn test_fetch_seed_peers() -> eyre::Result<()> {
-        let ignored_peers = Default::default();
+        let peer_filter = Default::default();
+        let sources = builtin_seed_sources(&HyperliquidChain::Mainnet);
+        let mut cache = SeedCache::default();
         let seed_peers =
-            fetch_hyperliquid_seed_peers(HyperliquidChain::Mainnet, &ignored_peers).await?;
+            fetch_hyperliquid_seed_peers(&sources, &peer_filter, &mut cache, 1).await?;
 
         assert!(!seed_peers.is_empty(), "Should have at least one entry");
 
@@ -1,16 +1,52 @@
-use std::{fs::File, path::PathBuf};
+use std::{
+    fs::{self, File, Permissions},
+    io::Write,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
 
 use eyre::{Context, ContextCompat};
-use serde::Deserialize;
-use tracing::debug;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tempfile::NamedTempFile;
+use tracing::{debug, info, warn};
 
-use crate::hl_gossip_config::HyperliquidChain;
+use crate::{gpg::verify_detached_signature, hl_gossip_config::HyperliquidChain};
 
 #[derive(Debug, Deserialize)]
 pub struct VisorConfig {
     pub chain: HyperliquidChain,
 }
 
+/// hl-visor installation for chains without hl-visor in the image, e.g. local devnets & private test chains
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VisorSetup {
+    /// Where to download hl-visor from when it is not installed yet
+    pub binary_url: String,
+    /// Detached signature of hl-visor, verified with gpg using current user's keyring
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature_url: Option<String>,
+    /// visor.json keys besides `chain`
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub config: Map<String, Value>,
+}
+
+/// Where installed hl-visor was downloaded from, kept next to the binary in `hl-visor.source.json`
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+struct VisorSource {
+    binary_url: String,
+    signature_url: Option<String>,
+}
+
+impl From<&VisorSetup> for VisorSource {
+    fn from(setup: &VisorSetup) -> Self {
+        Self {
+            binary_url: setup.binary_url.clone(),
+            signature_url: setup.signature_url.clone(),
+        }
+    }
+}
+
 pub fn read_hl_visor_config(config_file: Option<&PathBuf>) -> eyre::Result<VisorConfig> {
     let config_file = match config_file {
         Some(config_file) => config_file,
@@ -36,3 +72,141 @@ pub fn read_hl_visor_config(config_file: Option<&PathBuf>) -> eyre::Result<Visor
 
     Ok(config)
 }
+
+/// Writes visor.json for the chain & downloads hl-visor into `directory`, unless it is already there & was downloaded
+/// from the same URLs. Returns path to hl-visor.
+pub async fn install_hl_visor(
+    directory: &Path,
+    chain: &HyperliquidChain,
+    setup: &VisorSetup,
+) -> eyre::Result<PathBuf> {
+    fs::create_dir_all(directory)
+        .wrap_err_with(|| format!("failed to create hl-visor directory {directory:?}"))?;
+
+    write_visor_config(&directory.join("visor.json"), chain, setup)?;
+
+    let binary_path = directory.join("hl-visor");
+    let source_path = directory.join("hl-visor.source.json");
+    let source = VisorSource::from(setup);
+    if binary_path.is_file() {
+        let installed = fs::read(&source_path)
+            .ok()
+            .and_then(|content| serde_json::from_slice::<VisorSource>(&content).ok());
+        if installed.as_ref() == Some(&source) {
+            debug!(?binary_path, "hl-visor already installed");
+            return Ok(binary_path);
+        }
+
+        info!(
+            ?binary_path,
+            ?installed,
+            "installed hl-visor was downloaded from a different source, replacing it"
+        );
+    }
+
+    info!(url = setup.binary_url, ?binary_path, "downloading hl-visor");
+    let client = reqwest::Client::new();
+    let mut binary = NamedTempFile::new_in(directory)?;
+    download(&client, &setup.binary_url, binary.as_file_mut()).await?;
+
+    match &setup.signature_url {
+        Some(signature_url) => {
+            let mut signature = NamedTempFile::new_in(directory)?;
+            download(&client, signature_url, signature.as_file_mut()).await?;
+
+            verify_detached_signature(signature.path(), binary.path())
+                .await
+                .wrap_err("failed to verify hl-visor")?;
+            debug!("hl-visor signature verified");
+        }
+        None => warn!(
+            url = setup.binary_url,
+            "no hl-visor signature URL configured, not verifying downloaded binary"
+        ),
+    }
+
+    binary
+        .as_file()
+        .set_permissions(Permissions::from_mode(0o755))?;
+    binary
+        .persist(&binary_path)
+        .wrap_err("failed to install hl-visor")?;
+
+    let mut source_file = NamedTempFile::new_in(directory)?;
+    serde_json::to_writer(&mut source_file, &source).wrap_err("failed to write hl-visor source")?;
+    source_file
+        .persist(&source_path)
+        .wrap_err("failed to record hl-visor source")?;
+
+    Ok(binary_path)
+}
+
+fn write_visor_config(
+    path: &Path,
+    chain: &HyperliquidChain,
+    setup: &VisorSetup,
+) -> eyre::Result<()> {
+    let mut config = setup.config.clone();
+    config.insert("chain".to_string(), serde_json::to_value(chain)?);
+    let config = Value::Object(config);
+
+    let existing = fs::read(path)
+        .ok()
+        .and_then(|content| serde_json::from_slice::<Value>(&content).ok());
+    if existing.as_ref() == Some(&config) {
+        return Ok(());
+    }
+
+    let directory = path
+        .parent()
+        .wrap_err("failed to determine visor.json directory")?;
+    let mut file = NamedTempFile::new_in(directory)?;
+    serde_json::to_writer(&mut file, &config).wrap_err("failed to write visor.json")?;
+    file.persist(path)
+        .wrap_err("failed to replace visor.json")?;
+
+    info!(?path, ?chain, "wrote hl-visor configuration");
+
+    Ok(())
+}
+
+async fn download(client: &reqwest::Client, url: &str, file: &mut File) -> eyre::Result<()> {
+    let content = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .wrap_err_with(|| format!("failed to download {url}"))?
+        .bytes()
+        .await
+        .wrap_err_with(|| format!("failed to download {url}"))?;
+    file.write_all(&content)
+        .wrap_err_with(|| format!("failed to save {url}"))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_custom_chain_round_trip() -> eyre::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("visor.json");
+        let setup: VisorSetup = serde_json::from_str(
+            r#"{"binary_url": "http://127.0.0.1/hl-visor", "config": {"extra": 1}}"#,
+        )?;
+
+        write_visor_config(&path, &"Devnet".parse()?, &setup)?;
+        let config = read_hl_visor_config(Some(&path))?;
+        assert_eq!(config.chain, HyperliquidChain::Custom("Devnet".to_string()));
+
+        let content: Value = serde_json::from_slice(&fs::read(&path)?)?;
+        assert_eq!(content["chain"], "Devnet");
+        assert_eq!(content["extra"], 1);
+
+        Ok(())
+    }
+}
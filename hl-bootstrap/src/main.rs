@@ -1,18 +1,20 @@
 use std::{
-    collections::HashSet,
     env::current_dir,
     ffi::OsString,
     fs::{self},
     net::{Ipv4Addr, SocketAddr},
-    path::PathBuf,
-    process::Command,
+    path::{Path, PathBuf},
+    process::ExitStatus,
+    time::Duration,
 };
 
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 use duration_string::DurationString;
-use eyre::{Context, ContextCompat, bail};
-use tempfile::NamedTempFile;
-use tokio::runtime::{Builder, Runtime};
+use eyre::{Context, bail};
+use tokio::{
+    runtime::{Builder, Runtime},
+    sync::mpsc,
+};
 use tracing::{debug, error, info, level_filters::LevelFilter, trace, warn};
 use tracing_subscriber::{
     EnvFilter,
@@ -21,21 +23,55 @@ use tracing_subscriber::{
     util::SubscriberInitExt,
 };
 
+mod chain_config;
+mod child;
+mod child_output;
+mod commands;
+mod crashes;
+mod doctor;
+mod gossip_diff;
+mod gossip_logs;
+mod gossip_validation;
+mod gpg;
 mod hl_gossip_config;
 mod hl_visor_config;
 mod monitor;
+mod peer_db;
+mod peer_filter;
 mod prune;
+mod rotation;
+mod seed_cache;
+mod seed_peers;
+mod seed_source;
+mod selection;
 mod speedtest;
 mod sysctl;
+mod validator;
+mod watchdog;
 
 use crate::{
+    chain_config::ChainConfigs,
+    child::{RestartPolicy, SupervisorOptions, exit_code, run_child},
+    commands::{Command, run_command},
+    crashes::crash_scan_task,
+    doctor::preflight,
+    gossip_validation::check_gossip_config,
     hl_gossip_config::{
-        HyperliquidChain, HyperliquidSeedPeer, OverrideGossipConfig, fetch_hyperliquid_seed_peers,
+        HyperliquidChain, MergePolicies, MergePolicy, NodeRole, parse_reserved_peer_ip,
     },
-    hl_visor_config::read_hl_visor_config,
+    hl_visor_config::{install_hl_visor, read_hl_visor_config},
+    peer_filter::{ExtraSeedPeer, IgnoreRule, PeerFilter},
     prune::prune_worker_task,
-    speedtest::speedtest_nodes,
-    sysctl::read_sysctl,
+    rotation::{RotationOptions, rotate_seed_peers, rotation_worker_task},
+    seed_peers::{
+        SeedPeersOptions, pick_gossip_seed_peers, read_existing_gossip_config, write_gossip_config,
+        write_private_gossip_config,
+    },
+    selection::SelectionStrategy,
+    speedtest::{GossipProbe, LatencyStatistic, PortRange, SpeedtestOptions},
+    sysctl::{SysctlScope, apply_sysctls},
+    validator::{RawSignerKey, SignerKey, prepare_node_config},
+    watchdog::{WatchdogOptions, watchdog_task},
 };
 
 #[derive(Clone, Debug, Parser)]
@@ -72,13 +108,163 @@ struct Cli {
     )]
     seed_peers_max_latency: DurationString,
 
-    /// Ignore known bad seed peers by IP
+    /// How many connections to make to every seed peer when measuring latency
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_SEED_PEERS_LATENCY_SAMPLES",
+        default_value_t = 3
+    )]
+    seed_peers_latency_samples: usize,
+
+    /// Which latency statistic to rank seed peers by
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_SEED_PEERS_LATENCY_STATISTIC",
+        value_enum,
+        default_value_t = LatencyStatistic::Median
+    )]
+    seed_peers_latency_statistic: LatencyStatistic,
+
+    /// Gossip port or port range (e.g. `4000-4010`) to measure seed peer latency on. Gossip port is 4001 as of 2025-07-23
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_SEED_PEERS_GOSSIP_PORTS",
+        default_value = "4001"
+    )]
+    seed_peers_gossip_ports: PortRange,
+
+    /// Optional deeper check of seed peers, excluding ones which accept connections but don't behave like a gossip
+    /// endpoint. `hold` requires the connection to stay open, `banner` requires the peer to send something
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_SEED_PEERS_GOSSIP_PROBE",
+        value_enum,
+        default_value_t = GossipProbe::Off
+    )]
+    seed_peers_gossip_probe: GossipProbe,
+
+    /// How long the gossip probe waits on the connection
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_SEED_PEERS_GOSSIP_PROBE_TIMEOUT",
+        default_value = "2s"
+    )]
+    seed_peers_gossip_probe_timeout: DurationString,
+
+    /// How to pick seed peers among the ones passing latency threshold
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_SEED_PEERS_SELECTION",
+        value_enum,
+        default_value_t = SelectionStrategy::Latency
+    )]
+    seed_peers_selection: SelectionStrategy,
+
+    /// Ignore known bad seed peers by IP, CIDR block (`1.2.0.0/16`) or operator name pattern (`*cloud*`, or
+    /// `operator:<pattern>` for names that look like addresses)
     #[arg(long, env = "HL_BOOTSTRAP_SEED_PEERS_IGNORED", value_delimiter = ',')]
-    seed_peers_ignored: Vec<Ipv4Addr>,
+    seed_peers_ignored: Vec<IgnoreRule>,
+
+    /// Whether to consider seed peers in private, reserved & other non-routable ranges
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_SEED_PEERS_ALLOW_PRIVATE",
+        default_value_t = false
+    )]
+    seed_peers_allow_private: bool,
+
+    /// Only consider seed peers reported by at least this many distinct seed sources. Extra & learned peers are
+    /// not subject to quorum
+    #[arg(long, env = "HL_BOOTSTRAP_SEED_PEERS_QUORUM", default_value_t = 1)]
+    seed_peers_quorum: usize,
 
-    /// Extra seed peers to consider
+    /// Extra seed peers to consider, as `ip` or `ip=label`
     #[arg(long, env = "HL_BOOTSTRAP_SEED_PEERS_EXTRA", value_delimiter = ',')]
-    seed_peers_extra: Vec<Ipv4Addr>,
+    seed_peers_extra: Vec<ExtraSeedPeer>,
+
+    /// How many peers learned from hl-node gossip_connections logs to consider as seed peers, 0 disables
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_SEED_PEERS_LEARNED_LIMIT",
+        default_value_t = 16
+    )]
+    seed_peers_learned_limit: usize,
+
+    /// How old gossip_connections logs can be to learn seed peers from
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_SEED_PEERS_LEARNED_MAX_AGE",
+        default_value = "24h"
+    )]
+    seed_peers_learned_max_age: DurationString,
+
+    /// Per-chain configuration JSON file, used to declare additional seed peer sources & custom chains
+    #[arg(long, env = "HL_BOOTSTRAP_CHAIN_CONFIG_PATH")]
+    chain_config_path: Option<PathBuf>,
+
+    /// Where hl-visor & visor.json get installed for chains with `visor` in chain config, defaults to
+    /// hl-bootstrap/bin in the data directory
+    #[arg(long, env = "HL_BOOTSTRAP_VISOR_DIRECTORY")]
+    visor_directory: Option<PathBuf>,
+
+    /// How to combine discovered seed peers with root_node_ips in existing override_gossip_config.json
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_GOSSIP_CONFIG_MERGE_ROOT_NODE_IPS",
+        value_enum,
+        default_value_t = MergePolicy::Override
+    )]
+    gossip_config_merge_root_node_ips: MergePolicy,
+
+    /// How to combine try_new_peers in existing override_gossip_config.json with the default
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_GOSSIP_CONFIG_MERGE_TRY_NEW_PEERS",
+        value_enum,
+        default_value_t = MergePolicy::Keep
+    )]
+    gossip_config_merge_try_new_peers: MergePolicy,
+
+    /// How to combine n_gossip_peers in existing override_gossip_config.json with the computed one
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_GOSSIP_CONFIG_MERGE_N_GOSSIP_PEERS",
+        value_enum,
+        default_value_t = MergePolicy::Keep
+    )]
+    gossip_config_merge_n_gossip_peers: MergePolicy,
+
+    /// How to combine reserved_peer_ips in existing override_gossip_config.json with the configured ones
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_GOSSIP_CONFIG_MERGE_RESERVED_PEER_IPS",
+        value_enum,
+        default_value_t = MergePolicy::Union
+    )]
+    gossip_config_merge_reserved_peer_ips: MergePolicy,
+
+    /// Move unparseable override_gossip_config.json aside as `<path>.invalid` & write a new one, instead of failing
+    #[arg(long, env = "HL_BOOTSTRAP_GOSSIP_CONFIG_REPLACE_INVALID")]
+    gossip_config_replace_invalid: bool,
+
+    /// Node's place in a sentry topology. Sentries always accept private nodes given via --reserved-peers, private
+    /// nodes only gossip with sentries given via --reserved-peers
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_NODE_ROLE",
+        value_enum,
+        default_value_t = NodeRole::Standalone
+    )]
+    node_role: NodeRole,
+
+    /// Peers which are always accepted (reserved_peer_ips). Private node IPs on sentries, sentry IPs on private nodes
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_RESERVED_PEERS",
+        value_delimiter = ',',
+        value_parser = parse_reserved_peer_ip
+    )]
+    reserved_peers: Vec<Ipv4Addr>,
 
     /// Whether to ignore net.ipv6.conf.all.disable_ipv6 == 1. Due to hl-node bug, IPv6 being available to the node breaks it.
     #[arg(
@@ -88,6 +274,155 @@ struct Cli {
     )]
     ignore_ipv6_enabled: bool,
 
+    /// Scopes in which recommended sysctls deviating from their expected range are written on startup, requires root.
+    /// Per network namespace ones count as host-wide when the network namespace is shared with the host or that can't
+    /// be determined
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_APPLY_SYSCTLS",
+        value_enum,
+        value_delimiter = ','
+    )]
+    apply_sysctls: Vec<SysctlScope>,
+
+    /// Doctor checks which stop the startup when failing, others are only logged. See `doctor` subcommand
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_DOCTOR_FATAL_CHECKS",
+        value_delimiter = ',',
+        default_value = "data_directory"
+    )]
+    doctor_fatal_checks: Vec<String>,
+
+    /// Node data directory, defaults to current working directory
+    #[arg(long, env = "HL_BOOTSTRAP_DATA_DIRECTORY")]
+    data_directory: Option<PathBuf>,
+
+    /// Peer reputation database path, defaults to hl-bootstrap/peers.json in the data directory
+    #[arg(long, env = "HL_BOOTSTRAP_PEER_DB_PATH")]
+    peer_db_path: Option<PathBuf>,
+
+    /// Seed source cache path, defaults to hl-bootstrap/seed_cache.json in the data directory
+    #[arg(long, env = "HL_BOOTSTRAP_SEED_CACHE_PATH")]
+    seed_cache_path: Option<PathBuf>,
+
+    /// How old cached seed source content can be to still be used when the source is unavailable
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_SEED_CACHE_MAX_STALENESS",
+        default_value = "7d"
+    )]
+    seed_cache_max_staleness: DurationString,
+
+    /// Whether to spawn seed peer rotation task, periodically re-measuring seed peers while hl-visor is running
+    #[arg(long, env = "HL_BOOTSTRAP_SEED_PEERS_ROTATION_INTERVAL")]
+    seed_peers_rotation_interval: Option<DurationString>,
+
+    /// Relative score improvement over current seed peers required to rotate them, e.g. 0.25 for 25% better
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_SEED_PEERS_ROTATION_MIN_IMPROVEMENT",
+        default_value_t = 0.25
+    )]
+    seed_peers_rotation_min_improvement: f64,
+
+    /// How long node drift has to stay above healthy threshold for seed peers to be rotated. Requires metrics
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_SEED_PEERS_ROTATION_DRIFT_GRACE",
+        default_value = "5m"
+    )]
+    seed_peers_rotation_drift_grace: DurationString,
+
+    /// Whether to restart hl-visor after rotating seed peers. hl-node only reads gossip config on startup
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_SEED_PEERS_ROTATION_RESTART",
+        default_value_t = false
+    )]
+    seed_peers_rotation_restart: bool,
+
+    /// Whether to restart hl-visor when it exits, instead of exiting along with it
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_RESTART_POLICY",
+        value_enum,
+        default_value_t = RestartPolicy::Never
+    )]
+    restart_policy: RestartPolicy,
+
+    /// Delay before restarting hl-visor, doubled for every consecutive crash
+    #[arg(long, env = "HL_BOOTSTRAP_RESTART_BACKOFF", default_value = "1s")]
+    restart_backoff: DurationString,
+
+    /// Upper bound of the delay before restarting hl-visor
+    #[arg(long, env = "HL_BOOTSTRAP_RESTART_BACKOFF_MAX", default_value = "5m")]
+    restart_backoff_max: DurationString,
+
+    /// hl-visor exiting sooner than this after start counts as a crash
+    #[arg(long, env = "HL_BOOTSTRAP_RESTART_MIN_UPTIME", default_value = "2m")]
+    restart_min_uptime: DurationString,
+
+    /// Consecutive crashes after which hl-bootstrap gives up & exits
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_RESTART_CRASH_LOOP_THRESHOLD",
+        default_value_t = 5
+    )]
+    restart_crash_loop_threshold: u32,
+
+    /// How long hl-visor is given to exit after forwarded SIGTERM or SIGINT before it gets killed
+    #[arg(long, env = "HL_BOOTSTRAP_STOP_TIMEOUT", default_value = "30s")]
+    stop_timeout: DurationString,
+
+    /// Whether to capture hl-visor's stdout & stderr, logging each line with its parsed level & hints on known failures
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_CAPTURE_CHILD_OUTPUT",
+        default_value_t = false
+    )]
+    capture_child_output: bool,
+
+    /// Whether to re-measure & pick seed peers before restarting hl-visor after a crash
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_RESTART_RESELECT_SEED_PEERS",
+        default_value_t = false
+    )]
+    restart_reselect_seed_peers: bool,
+
+    /// Restart hl-visor when node drift stays above this for --watchdog-drift-grace
+    #[arg(long, env = "HL_BOOTSTRAP_WATCHDOG_DRIFT_THRESHOLD")]
+    watchdog_drift_threshold: Option<DurationString>,
+
+    /// How long node drift has to stay above watchdog threshold for hl-visor to be restarted
+    #[arg(long, env = "HL_BOOTSTRAP_WATCHDOG_DRIFT_GRACE", default_value = "15m")]
+    watchdog_drift_grace: DurationString,
+
+    /// Restart hl-visor when exchange time does not advance for this long
+    #[arg(long, env = "HL_BOOTSTRAP_WATCHDOG_STALL_TIMEOUT")]
+    watchdog_stall_timeout: Option<DurationString>,
+
+    /// Maximum watchdog restarts within --watchdog-restart-window, to avoid flapping
+    #[arg(long, env = "HL_BOOTSTRAP_WATCHDOG_MAX_RESTARTS", default_value_t = 3)]
+    watchdog_max_restarts: usize,
+
+    /// Period watchdog restarts are counted over
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_WATCHDOG_RESTART_WINDOW",
+        default_value = "1h"
+    )]
+    watchdog_restart_window: DurationString,
+
+    /// Whether to re-measure & pick seed peers before watchdog restarts hl-visor
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_WATCHDOG_RESELECT_SEED_PEERS",
+        default_value_t = false
+    )]
+    watchdog_reselect_seed_peers: bool,
+
     /// Whether to spawn data directory pruning task. This is used when hl-bootstrap has child process to execute
     #[arg(long, env = "HL_BOOTSTRAP_PRUNE_DATA_INTERVAL")]
     prune_data_interval: Option<DurationString>,
@@ -116,20 +451,258 @@ struct Cli {
     )]
     metrics_healthy_drift_threshold: DurationString,
 
+    /// Validator signer key file, must only be accessible by the owner. Used with run-validator
+    #[arg(long, env = "HL_BOOTSTRAP_VALIDATOR_SIGNER_KEY_PATH")]
+    validator_signer_key_path: Option<PathBuf>,
+
+    /// Validator signer key, as an alternative to the key file. Used with run-validator
+    #[arg(
+        long,
+        env = "HL_BOOTSTRAP_VALIDATOR_SIGNER_KEY",
+        hide_env_values = true
+    )]
+    validator_signer_key: Option<RawSignerKey>,
+
+    /// Sentry node IPs the validator gossips through, replacing discovered seed peers. Used with run-validator
+    #[arg(long, env = "HL_BOOTSTRAP_VALIDATOR_SENTRY_IPS", value_delimiter = ',')]
+    validator_sentry_ips: Vec<Ipv4Addr>,
+
+    /// node_config.json path, defaults to hl/hyperliquid_data/node_config.json in the data directory
+    #[arg(long, env = "HL_BOOTSTRAP_VALIDATOR_NODE_CONFIG_PATH")]
+    validator_node_config_path: Option<PathBuf>,
+
     /// Chain to set up configuration for
     #[arg(long, env = "HL_BOOTSTRAP_NETWORK")]
     network: Option<HyperliquidChain>,
 
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Free form args to execute after the setup
     args: Vec<OsString>,
 }
 
+impl Cli {
+    fn data_directory(&self) -> eyre::Result<PathBuf> {
+        match &self.data_directory {
+            Some(data_directory) => Ok(data_directory.clone()),
+            None => current_dir().wrap_err("failed to get current working directory"),
+        }
+    }
+
+    /// Directory for hl-bootstrap's own state, kept outside of node's hl/data which gets pruned
+    fn state_directory(&self) -> eyre::Result<PathBuf> {
+        Ok(self.data_directory()?.join("hl-bootstrap"))
+    }
+
+    /// Whether hl-bootstrap has to stay around as hl-visor's parent to run background tasks
+    fn needs_supervision(&self) -> bool {
+        self.prune_data_interval.is_some()
+            || self.restart_policy != RestartPolicy::Never
+            || self.capture_child_output
+            || self.watchdog_options().is_some()
+            || self.metrics_listen_address.is_some()
+            || self.seed_peers_rotation_interval().is_some()
+    }
+
+    fn is_validator(&self) -> bool {
+        self.args.first().is_some_and(|arg| arg == "run-validator")
+    }
+
+    /// Sentry nodes to exclusively gossip with, for validators behind sentries & private nodes
+    fn upstream_sentry_ips(&self) -> Option<&[Ipv4Addr]> {
+        if self.is_validator() && !self.validator_sentry_ips.is_empty() {
+            Some(&self.validator_sentry_ips)
+        } else if self.node_role == NodeRole::Private {
+            Some(&self.reserved_peers)
+        } else {
+            None
+        }
+    }
+
+    /// Nodes behind sentries have fixed peers, so there's nothing to rotate
+    fn seed_peers_rotation_interval(&self) -> Option<DurationString> {
+        self.seed_peers_rotation_interval
+            .filter(|_| self.upstream_sentry_ips().is_none())
+    }
+
+    fn validator_node_config_path(&self) -> eyre::Result<PathBuf> {
+        match &self.validator_node_config_path {
+            Some(path) => Ok(path.clone()),
+            None => Ok(self
+                .data_directory()?
+                .join("hl/hyperliquid_data/node_config.json")),
+        }
+    }
+
+    /// Chain from args, or from visor.json next to hl-visor in PATH
+    fn network(&self) -> eyre::Result<HyperliquidChain> {
+        Ok(match &self.network {
+            Some(network) => {
+                debug!(?network, "network specified via args");
+                network.clone()
+            }
+            None => {
+                debug!("no network specified, reading from hl-visor configuration");
+                let config = read_hl_visor_config(self.visor_config_path.as_ref())?;
+
+                debug!(network = ?config.chain, "read hl-visor configuration");
+                config.chain
+            }
+        })
+    }
+
+    /// Chain in visor.json hl-visor is going to use, when it can be read
+    fn visor_chain(&self, network: &HyperliquidChain) -> Option<HyperliquidChain> {
+        let installed = self
+            .chain_configs()
+            .ok()?
+            .get(network)
+            .is_some_and(|config| config.visor.is_some());
+        let config_path = match installed {
+            true => Some(self.visor_directory().ok()?.join("visor.json")),
+            false => self.visor_config_path.clone(),
+        };
+
+        read_hl_visor_config(config_path.as_ref())
+            .inspect_err(|err| debug!(?err, "unable to read hl-visor configuration"))
+            .ok()
+            .map(|config| config.chain)
+    }
+
+    fn seed_peers_options(&self) -> eyre::Result<SeedPeersOptions> {
+        let network = self.network()?;
+
+        Ok(SeedPeersOptions {
+            visor_chain: self.visor_chain(&network),
+            sources: self.chain_configs()?.seed_sources(&network),
+            network,
+            peer_filter: PeerFilter::new(
+                self.seed_peers_ignored.clone(),
+                self.seed_peers_allow_private,
+            ),
+            quorum: self.seed_peers_quorum,
+            extra: self.seed_peers_extra.clone(),
+            learned_limit: self.seed_peers_learned_limit,
+            learned_max_age: self.seed_peers_learned_max_age.into(),
+            data_directory: self.data_directory()?,
+            peer_db_path: self.peer_db_path()?,
+            seed_cache_path: self.seed_cache_path()?,
+            seed_cache_max_staleness: self.seed_cache_max_staleness.into(),
+            amount: self.seed_peers_amount,
+            selection: self.seed_peers_selection,
+            speedtest: self.speedtest_options(),
+            gossip_config_path: self.override_gossip_config_path.clone(),
+            node_role: self.node_role,
+            reserved_peers: self.reserved_peers.clone(),
+            merge_policies: MergePolicies {
+                root_node_ips: self.gossip_config_merge_root_node_ips,
+                try_new_peers: self.gossip_config_merge_try_new_peers,
+                n_gossip_peers: self.gossip_config_merge_n_gossip_peers,
+                reserved_peer_ips: self.gossip_config_merge_reserved_peer_ips,
+            },
+            replace_invalid_gossip_config: self.gossip_config_replace_invalid,
+            dry_run: false,
+        })
+    }
+
+    fn rotation_options(&self) -> Option<RotationOptions> {
+        self.seed_peers_rotation_interval()
+            .map(|interval| RotationOptions {
+                interval: interval.into(),
+                min_improvement: self.seed_peers_rotation_min_improvement,
+                drift_threshold: self.metrics_healthy_drift_threshold.into(),
+                drift_grace: self.seed_peers_rotation_drift_grace.into(),
+            })
+    }
+
+    fn supervisor_options(&self) -> SupervisorOptions {
+        SupervisorOptions {
+            restart_policy: self.restart_policy,
+            backoff_initial: self.restart_backoff.into(),
+            backoff_max: self.restart_backoff_max.into(),
+            min_uptime: self.restart_min_uptime.into(),
+            crash_loop_threshold: self.restart_crash_loop_threshold,
+            stop_timeout: self.stop_timeout.into(),
+            capture_output: self.capture_child_output,
+        }
+    }
+
+    /// Watchdog is enabled with either of the triggers
+    fn watchdog_options(&self) -> Option<WatchdogOptions> {
+        if self.watchdog_drift_threshold.is_none() && self.watchdog_stall_timeout.is_none() {
+            return None;
+        }
+
+        Some(WatchdogOptions {
+            drift_threshold: self.watchdog_drift_threshold.map(Into::into),
+            drift_grace: self.watchdog_drift_grace.into(),
+            stall_timeout: self.watchdog_stall_timeout.map(Into::into),
+            max_restarts: self.watchdog_max_restarts,
+            restart_window: self.watchdog_restart_window.into(),
+        })
+    }
+
+    fn speedtest_options(&self) -> SpeedtestOptions {
+        SpeedtestOptions {
+            samples: self.seed_peers_latency_samples,
+            ports: self.seed_peers_gossip_ports,
+            rank_by: self.seed_peers_latency_statistic,
+            max_latency: self.seed_peers_max_latency.into(),
+            probe: self.seed_peers_gossip_probe,
+            probe_timeout: self.seed_peers_gossip_probe_timeout.into(),
+        }
+    }
+
+    fn chain_configs(&self) -> eyre::Result<ChainConfigs> {
+        match &self.chain_config_path {
+            Some(path) => ChainConfigs::load(path),
+            None => Ok(ChainConfigs::default()),
+        }
+    }
+
+    fn visor_directory(&self) -> eyre::Result<PathBuf> {
+        match &self.visor_directory {
+            Some(path) => Ok(path.clone()),
+            None => Ok(self.state_directory()?.join("bin")),
+        }
+    }
+
+    fn seed_cache_path(&self) -> eyre::Result<PathBuf> {
+        match &self.seed_cache_path {
+            Some(path) => Ok(path.clone()),
+            None => Ok(self.state_directory()?.join("seed_cache.json")),
+        }
+    }
+
+    fn peer_db_path(&self) -> eyre::Result<PathBuf> {
+        match &self.peer_db_path {
+            Some(path) => Ok(path.clone()),
+            None => Ok(self.state_directory()?.join("peers.json")),
+        }
+    }
+}
+
 fn main() -> eyre::Result<()> {
-    let args = Cli::parse();
+    let mut args = Cli::parse();
+
+    // ENTRYPOINT passes everything after `--` as free form args, so parse again without the separator when those
+    // start with a subcommand
+    if let Some(first_arg) = args.args.first()
+        && Cli::command().find_subcommand(first_arg).is_some()
+    {
+        let mut separator_seen = false;
+        args = Cli::parse_from(std::env::args_os().filter(|arg| {
+            let is_separator = !separator_seen && arg == "--";
+            separator_seen |= is_separator;
+            !is_separator
+        }));
+    }
 
     // As hl-bootstrap is usually used in ENTRYPOINT, then bail out when first argument is
     // obviously not related to Hyperliquid (e.g. just running bash, for whatever purpose)
-    if let Some(first_arg) = args.args.first()
+    if args.command.is_none()
+        && let Some(first_arg) = args.args.first()
         && first_arg != "run-non-validator"
         && first_arg != "run-validator"
     {
@@ -157,7 +730,14 @@ fn main() -> eyre::Result<()> {
 
     trace!(?args, "args");
 
-    let use_mt = args.prune_data_interval.is_some() || args.metrics_listen_address.is_some();
+    if let Some(command) = &args.command {
+        return Builder::new_current_thread()
+            .enable_all()
+            .build()?
+            .block_on(run_command(command, &args));
+    }
+
+    let use_mt = args.needs_supervision();
 
     let runtime = if use_mt {
         Builder::new_multi_thread()
@@ -166,6 +746,11 @@ fn main() -> eyre::Result<()> {
     }
     .enable_all()
     .build()?;
+    let visor = runtime.block_on(prepare_hl_visor(&args))?;
+    if !args.apply_sysctls.is_empty() {
+        apply_sysctls(&args.apply_sysctls);
+    }
+    preflight(&args, &args.doctor_fatal_checks)?;
     runtime.block_on(prepare_hl_node(&args))?;
 
     if args.args.is_empty() {
@@ -173,43 +758,52 @@ fn main() -> eyre::Result<()> {
         return Ok(());
     }
 
-    run_node(runtime, &args)?;
+    let status = run_node(runtime, &args, &visor)?;
+    if !status.success() {
+        std::process::exit(exit_code(&status));
+    }
 
     Ok(())
 }
 
-fn run_node(rt: Runtime, args: &Cli) -> eyre::Result<()> {
-    info!(args = ?args.args, "setup done, executing hl-visor");
+fn run_node(rt: Runtime, args: &Cli, visor: &Path) -> eyre::Result<ExitStatus> {
+    info!(?visor, args = ?args.args, "setup done, executing hl-visor");
 
-    if args.prune_data_interval.is_none() && args.metrics_listen_address.is_none() {
+    if !args.needs_supervision() {
         // Just exec into the child
-        let err = exec::Command::new("hl-visor").args(&args.args).exec();
+        let err = exec::Command::new(visor).args(&args.args).exec();
         error!(?err, ?args.args, "failed to exec");
         std::process::exit(1);
     }
 
-    // TODO: configurable in future
-    let data_directory = current_dir().wrap_err("failed to get current working directory")?;
+    let data_directory = args.data_directory()?;
 
-    let _prune_task = args.prune_data_interval.map(|prune_interval| {
+    let prune_task = args.prune_data_interval.map(|prune_interval| {
         rt.spawn({
             let prune_data_older_than = args.prune_data_older_than;
 
             prune_worker_task(
-                data_directory,
+                data_directory.clone(),
                 prune_interval.into(),
                 prune_data_older_than.into(),
             )
         })
     });
 
-    let _poll_task = args.metrics_listen_address.is_some().then(|| {
-        rt.spawn(crate::monitor::poll_node(
-            args.metrics_status_poll_interval.into(),
-        ))
-    });
+    // Watchdog relies on node state polled for metrics
+    let poll_task = (args.metrics_listen_address.is_some() || args.watchdog_options().is_some())
+        .then(|| {
+            rt.spawn(crate::monitor::poll_node(
+                args.metrics_status_poll_interval.into(),
+            ))
+        });
+
+    let crash_scan_task = args
+        .metrics_listen_address
+        .is_some()
+        .then(|| rt.spawn(crash_scan_task(data_directory, CRASH_SCAN_INTERVAL)));
 
-    let _metrics_server = args.metrics_listen_address.map(|address| {
+    let metrics_server = args.metrics_listen_address.map(|address| {
         let metrics_healthy_drift_threshold = args.metrics_healthy_drift_threshold.into();
         // Default node URL is localhost:3001 (where hl-visor serves /info)
         let node_url = std::env::var("HL_BOOTSTRAP_NODE_URL")
@@ -229,45 +823,129 @@ fn run_node(rt: Runtime, args: &Cli) -> eyre::Result<()> {
         })
     });
 
-    let mut child = Command::new("hl-visor")
-        .args(&args.args)
-        .spawn()
-        .wrap_err("failed to spawn child")?;
+    let (control_tx, control_rx) = mpsc::channel(1);
+    // Nodes behind sentries have fixed peers, so there's nothing to re-pick
+    let seed_peers = match args.upstream_sentry_ips() {
+        Some(_) => None,
+        None => Some(args.seed_peers_options()?),
+    };
 
-    child.wait().wrap_err("failed to wait for child")?;
+    let rotation_task =
+        args.rotation_options()
+            .zip(seed_peers.clone())
+            .map(|(options, seed_peers)| {
+                rt.spawn(rotation_worker_task(
+                    seed_peers,
+                    options,
+                    args.seed_peers_rotation_restart.then(|| control_tx.clone()),
+                ))
+            });
 
-    Ok(())
+    let watchdog_task = args.watchdog_options().map(|options| {
+        let reselect = seed_peers
+            .clone()
+            .filter(|_| args.watchdog_reselect_seed_peers);
+        rt.spawn(watchdog_task(options, reselect, control_tx.clone()))
+    });
+
+    // Crashing shortly after start is often caused by bad seed peers, unless they're fixed sentries
+    let reselect = seed_peers.filter(|_| args.restart_reselect_seed_peers);
+    let status = rt.block_on(run_child(
+        visor,
+        &args.args,
+        control_rx,
+        args.supervisor_options(),
+        async || {
+            let Some(seed_peers) = &reselect else {
+                return;
+            };
+            info!("re-selecting seed peers after hl-visor crash");
+            if let Err(err) = rotate_seed_peers(seed_peers, None).await {
+                warn!(?err, "failed to re-select seed peers");
+            }
+        },
+    ));
+
+    // Rotation & watchdog go first as they can touch gossip config & request restarts, metrics stay up the longest
+    let tasks = [
+        watchdog_task,
+        rotation_task,
+        prune_task,
+        crash_scan_task,
+        poll_task,
+        metrics_server,
+    ];
+    rt.block_on(async {
+        for task in tasks.into_iter().flatten() {
+            task.abort();
+            if let Err(err) = task.await
+                && !err.is_cancelled()
+            {
+                warn!(?err, "background task failed");
+            }
+        }
+    });
+    debug!("background tasks stopped");
+
+    status
+}
+
+/// hl-visor to run. Chains with `visor` in chain config get it installed into the visor directory, otherwise the
+/// one in PATH is used
+async fn prepare_hl_visor(args: &Cli) -> eyre::Result<PathBuf> {
+    let default = PathBuf::from("hl-visor");
+    // Without explicit network, it's read from visor.json next to hl-visor in PATH
+    let Some(network) = &args.network else {
+        return Ok(default);
+    };
+    let chain_configs = args.chain_configs()?;
+    let Some(setup) = chain_configs
+        .get(network)
+        .and_then(|config| config.visor.as_ref())
+    else {
+        return Ok(default);
+    };
+
+    install_hl_visor(&args.visor_directory()?, network, setup).await
 }
 
 async fn prepare_hl_node(args: &Cli) -> eyre::Result<()> {
-    if !args.ignore_ipv6_enabled {
-        let key_ipv6_all = "net.ipv6.conf.all.disable_ipv6";
-        if let Ok(value) = read_sysctl(key_ipv6_all)
-            && value == "0"
-        {
+    let seed_peers = args.seed_peers_options()?;
+    let network = &seed_peers.network;
+    info!(?network, role = ?args.node_role, "preparing hl-node configuration");
+
+    if args.node_role != NodeRole::Standalone && args.reserved_peers.is_empty() {
+        bail!(
+            "node role {:?} requires reserved peers ({})",
+            args.node_role,
+            match args.node_role {
+                NodeRole::Private => "sentry node IPs",
+                _ => "private node IPs",
+            }
+        );
+    }
+
+    if args.is_validator() {
+        let signer_key = match &args.validator_signer_key_path {
+            Some(path) => Some(SignerKey::load(path)?),
+            None => args
+                .validator_signer_key
+                .as_ref()
+                .map(|key| key.parse().wrap_err("invalid validator signer key"))
+                .transpose()?,
+        };
+        prepare_node_config(&args.validator_node_config_path()?, signer_key.as_ref())?;
+
+        if args.upstream_sentry_ips().is_none() {
             warn!(
-                key = key_ipv6_all,
-                value, "ipv6 appears to be enabled, node might not start up properly"
+                "no sentry nodes given, validator will gossip with discovered seed peers directly"
             );
         }
     }
 
-    let network = match args.network {
-        Some(network) => {
-            debug!(?network, "network specified via args");
-            network
-        }
-        None => {
-            debug!("no network specified, reading from hl-visor configuration");
-            let config = read_hl_visor_config(args.visor_config_path.as_ref())?;
-
-            debug!(network = ?config.chain, "read hl-visor configuration");
-            config.chain
-        }
-    };
-    info!(?network, "preparing hl-node configuration");
-
-    let ignored_seed_peers = HashSet::from_iter(args.seed_peers_ignored.clone());
+    if let Some(sentry_ips) = args.upstream_sentry_ips() {
+        return write_private_gossip_config(&seed_peers, sentry_ips);
+    }
 
     if let Ok(metadata) = fs::metadata(&args.override_gossip_config_path)
         && metadata.is_file()
@@ -281,78 +959,25 @@ async fn prepare_hl_node(args: &Cli) -> eyre::Result<()> {
             gossip_config_path = ?args.override_gossip_config_path,
             "gossip config last modified"
         );
-        if last_modified <= args.override_gossip_config_max_age {
+        // Unparseable configuration fails startup, unless it's moved aside & a new one is written
+        if last_modified <= args.override_gossip_config_max_age
+            && let Some(config) = read_existing_gossip_config(&seed_peers)?
+        {
             debug!(
                 ?mtime,
                 gossip_config_path = ?args.override_gossip_config_path,
                 "gossip config modified recently, not updating seed peers"
             );
-            return Ok(());
+            return check_gossip_config(&config, seed_peers.visor_chain.as_ref());
         }
     }
 
-    let config_path_directory = args
-        .override_gossip_config_path
-        .parent()
-        .wrap_err("failed to determine override_gossip_config.json directory")?;
+    let seed_nodes = pick_gossip_seed_peers(&seed_peers).await?;
 
-    // TODO: load existing configuration
-    let mut config = OverrideGossipConfig::new(network);
-
-    info!(?network, ?ignored_seed_peers, "fetching seed nodes");
-    let mut seed_nodes = fetch_hyperliquid_seed_peers(network, &ignored_seed_peers).await?;
-    info!(?network, count = seed_nodes.len(), "got seed nodes");
-
-    if !args.seed_peers_extra.is_empty() {
-        info!(
-            ?network,
-            count = args.seed_peers_extra.len(),
-            "including extra seed peers from args"
-        );
-        for extra_seed in &args.seed_peers_extra {
-            seed_nodes.push(HyperliquidSeedPeer {
-                operator_name: "manual".to_string(),
-                ip: *extra_seed,
-            });
-        }
-    }
-
-    if !seed_nodes.is_empty() {
-        let tested_seed_nodes = speedtest_nodes(
-            seed_nodes,
-            args.seed_peers_amount,
-            args.seed_peers_max_latency.into(),
-        )
-        .await
-        .wrap_err("failed to measure latency of seed nodes")?;
-
-        if tested_seed_nodes.is_empty() {
-            bail!(
-                "no seed nodes passed latency threshold, try increasing threshold (current: {})",
-                args.seed_peers_max_latency
-            );
-        }
-
-        for seed in tested_seed_nodes {
-            config.root_node_ips.push(seed.into());
-        }
-
-        // Adjust n_gossip_peers
-        // Allowed range is [1, 100]
-        // See https://github.com/hyperliquid-dex/node/blob/main/README_misc.md#additional-configuration
-        let n_gossip_peers = config.root_node_ips.len();
-        if n_gossip_peers > 8 {
-            config.n_gossip_peers = Some(n_gossip_peers.min(100) as u16);
-        }
-    }
-
-    let mut new_config_file = NamedTempFile::new_in(config_path_directory)?;
-    serde_json::to_writer(&mut new_config_file, &config)
-        .wrap_err("failed to write new configuration")?;
-
-    new_config_file
-        .persist(&args.override_gossip_config_path)
-        .wrap_err("failed to replace override_gossip_config.json")?;
+    write_gossip_config(&seed_peers, &seed_nodes)?;
 
     Ok(())
 }
+
+/// How often visor child stderr files are scanned for crashes when metrics are enabled
+const CRASH_SCAN_INTERVAL: Duration = Duration::from_secs(60);
@@ -4,8 +4,9 @@ use std::{
 };
 
 use prometheus::{
-    Gauge, Histogram, IntGauge, exponential_buckets, histogram_opts, register_gauge,
-    register_histogram, register_int_gauge,
+    Gauge, Histogram, IntCounterVec, IntGauge, IntGaugeVec, exponential_buckets, histogram_opts,
+    register_gauge, register_histogram, register_int_counter_vec, register_int_gauge,
+    register_int_gauge_vec,
 };
 use reqwest::{Client, ClientBuilder, Method, header::CONTENT_TYPE};
 use serde::Deserialize;
@@ -13,6 +14,7 @@ use tokio::time::{MissedTickBehavior, interval};
 use tracing::{trace, warn};
 
 pub mod server;
+pub mod sources;
 
 pub static GAUGE_HL_NODE_SYSTEM_TIME_MS: LazyLock<Gauge> = LazyLock::new(|| {
     register_gauge!(
@@ -47,6 +49,32 @@ pub static HISTOGRAM_HL_NODE_TIME_DRIFT_MS: LazyLock<Histogram> = LazyLock::new(
     .unwrap()
 });
 
+pub static COUNTER_HL_VISOR_RESTARTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "hl_visor_restarts",
+        "Times hl-visor was restarted by hl-bootstrap, by reason",
+        &["reason"]
+    )
+    .unwrap()
+});
+
+pub static GAUGE_HL_VISOR_LAST_EXIT_CODE: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!(
+        "hl_visor_last_exit_code",
+        "Exit code of last hl-visor run, 128 + signal number when killed by a signal"
+    )
+    .unwrap()
+});
+
+pub static GAUGE_HL_NODE_CRASHES: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "hl_node_crashes",
+        "Panics found in visor child stderr files still on disk, by crash signature ID (see `crashes` subcommand) & hardfork version",
+        &["signature_id", "hardfork"]
+    )
+    .unwrap()
+});
+
 fn init_metrics() {
     LazyLock::force(&GAUGE_HL_NODE_SYSTEM_TIME_MS);
     LazyLock::force(&GAUGE_HL_NODE_TIME_MS);
@@ -61,7 +89,7 @@ static CLIENT: LazyLock<Client> = LazyLock::new(|| {
         .unwrap()
 });
 
-async fn request_exchange_time() -> Result<u64, reqwest::Error> {
+pub async fn request_exchange_time() -> Result<u64, reqwest::Error> {
     #[derive(Deserialize)]
     struct ExchangeStatus {
         time: u64,
@@ -121,6 +149,16 @@ pub async fn poll_node(poll_interval: Duration) {
     }
 }
 
+/// Last observed difference between system time & HyperCore exchange time, if the node is responding
+pub fn node_drift() -> Option<Duration> {
+    if GAUGE_HL_NODE_RESPONDING.get() != 1 {
+        return None;
+    }
+
+    let drift_ms = (GAUGE_HL_NODE_SYSTEM_TIME_MS.get() - GAUGE_HL_NODE_TIME_MS.get()).max(0.0);
+    Some(Duration::from_secs_f64(drift_ms / 1e3))
+}
+
 #[inline]
 const fn as_ms_f64(duration: &Duration) -> f64 {
     (duration.as_secs() as f64 * 1e3) + (duration.subsec_nanos() as f64 / 1e6)
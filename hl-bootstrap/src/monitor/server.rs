@@ -1,5 +1,5 @@
 use std::sync::LazyLock;
-use std::{net::SocketAddr, ops::Sub, time::Duration};
+use std::{net::SocketAddr, time::Duration};
 
 use axum::extract::Request;
 use axum::http::HeaderMap;
@@ -12,9 +12,7 @@ use reqwest::{Client, StatusCode};
 use tokio::net::TcpListener;
 use tracing::error;
 
-use crate::monitor::{
-    GAUGE_HL_NODE_RESPONDING, GAUGE_HL_NODE_SYSTEM_TIME_MS, GAUGE_HL_NODE_TIME_MS, as_ms_f64,
-};
+use crate::monitor::{GAUGE_HL_NODE_RESPONDING, node_drift, sources::seed_source_statuses};
 
 #[derive(Clone)]
 struct MonitorServer {
@@ -28,6 +26,7 @@ fn router() -> Router<MonitorServer> {
         .route("/metrics", get(metrics))
         .route("/livez", get(livez))
         .route("/readyz", get(readyz))
+        .route("/sources", get(sources))
         .route("/info", post(proxy_info))
         .route("/info", get(proxy_info))
 }
@@ -45,6 +44,17 @@ async fn metrics() -> impl IntoResponse {
     )
 }
 
+/// Last fetch state per seed source, including last error
+async fn sources() -> impl IntoResponse {
+    match serde_json::to_string(&seed_source_statuses()) {
+        Ok(body) => ([(CONTENT_TYPE, "application/json")], body).into_response(),
+        Err(err) => {
+            error!(?err, "failed to serialize seed source statuses");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
 async fn livez() -> impl IntoResponse {
     if GAUGE_HL_NODE_RESPONDING.get() == 1 {
         StatusCode::OK
@@ -54,13 +64,7 @@ async fn livez() -> impl IntoResponse {
 }
 
 async fn readyz(State(state): State<MonitorServer>) -> impl IntoResponse {
-    if GAUGE_HL_NODE_RESPONDING.get() == 1
-        && GAUGE_HL_NODE_SYSTEM_TIME_MS
-            .get()
-            .sub(GAUGE_HL_NODE_TIME_MS.get())
-            .max(0.0)
-            < as_ms_f64(&state.healthy_drift_threshold)
-    {
+    if node_drift().is_some_and(|drift| drift < state.healthy_drift_threshold) {
         StatusCode::OK
     } else {
         StatusCode::SERVICE_UNAVAILABLE
@@ -87,10 +91,10 @@ async fn proxy_info(State(state): State<MonitorServer>, request: Request) -> imp
     // Copy relevant headers (excluding host and connection)
     for (key, value) in headers.iter() {
         let header_name = key.as_str();
-        if !matches!(header_name, "host" | "connection" | "content-length") {
-            if let Ok(header_value) = value.to_str() {
-                proxy_request = proxy_request.header(header_name, header_value);
-            }
+        if !matches!(header_name, "host" | "connection" | "content-length")
+            && let Ok(header_value) = value.to_str()
+        {
+            proxy_request = proxy_request.header(header_name, header_value);
         }
     }
 
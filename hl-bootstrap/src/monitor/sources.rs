@@ -0,0 +1,184 @@
+use std::{
+    collections::BTreeMap,
+    sync::{LazyLock, Mutex},
+    time::Duration,
+};
+
+use prometheus::{
+    HistogramVec, IntCounterVec, IntGaugeVec, exponential_buckets, histogram_opts,
+    register_histogram_vec, register_int_counter_vec, register_int_gauge_vec,
+};
+use serde::Serialize;
+
+use crate::peer_db::unix_now;
+
+pub static COUNTER_SEED_SOURCE_FETCHES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "hl_bootstrap_seed_source_fetches",
+        "Seed source fetches by result: success, cached (failed, last known good copy used) or failure",
+        &["source", "result"]
+    )
+    .unwrap()
+});
+
+pub static GAUGE_SEED_SOURCE_CANDIDATES: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "hl_bootstrap_seed_source_candidates",
+        "Seed peer candidates the source provided during last fetch, after filtering",
+        &["source"]
+    )
+    .unwrap()
+});
+
+pub static GAUGE_SEED_SOURCE_IGNORED: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "hl_bootstrap_seed_source_ignored",
+        "Seed peers from the source which were filtered out during last fetch",
+        &["source"]
+    )
+    .unwrap()
+});
+
+pub static HISTOGRAM_SEED_SOURCE_FETCH_SECONDS: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        histogram_opts!(
+            "hl_bootstrap_seed_source_fetch_seconds",
+            "Seed source fetch duration in seconds",
+            exponential_buckets(0.005, 2.0, 14).unwrap()
+        ),
+        &["source"]
+    )
+    .unwrap()
+});
+
+pub static GAUGE_SEED_SOURCE_LAST_SUCCESS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "hl_bootstrap_seed_source_last_success",
+        "Unix timestamp in seconds of last successful fetch of the source",
+        &["source"]
+    )
+    .unwrap()
+});
+
+/// Per-source fetch state, served on /sources
+static SOURCE_STATUSES: LazyLock<Mutex<BTreeMap<String, SourceStatus>>> =
+    LazyLock::new(Default::default);
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct SourceStatus {
+    /// Unix timestamps in seconds
+    pub last_attempt: u64,
+    pub last_success: Option<u64>,
+    pub last_result: &'static str,
+    /// Error of the last fetch, also set when last known good copy was used instead
+    pub last_error: Option<String>,
+    pub candidates: usize,
+    pub ignored: usize,
+    pub fetch_ms: f64,
+}
+
+/// Outcome of a single seed source fetch
+pub enum FetchOutcome<'a> {
+    Success {
+        candidates: usize,
+        ignored: usize,
+    },
+    /// Source failed, but last known good copy was used
+    Cached {
+        candidates: usize,
+        ignored: usize,
+        err: &'a eyre::Report,
+    },
+    Failure {
+        err: &'a eyre::Report,
+    },
+}
+
+pub fn record_seed_source_fetch(source: &str, elapsed: Duration, outcome: FetchOutcome) {
+    let now = unix_now();
+    let mut statuses = SOURCE_STATUSES.lock().unwrap();
+    let status = statuses.entry(source.to_string()).or_default();
+    status.last_attempt = now;
+    status.fetch_ms = elapsed.as_secs_f64() * 1e3;
+
+    HISTOGRAM_SEED_SOURCE_FETCH_SECONDS
+        .with_label_values(&[source])
+        .observe(elapsed.as_secs_f64());
+
+    let (result, counts, err) = match outcome {
+        FetchOutcome::Success {
+            candidates,
+            ignored,
+        } => ("success", Some((candidates, ignored)), None),
+        FetchOutcome::Cached {
+            candidates,
+            ignored,
+            err,
+        } => ("cached", Some((candidates, ignored)), Some(err)),
+        FetchOutcome::Failure { err } => ("failure", None, Some(err)),
+    };
+
+    COUNTER_SEED_SOURCE_FETCHES
+        .with_label_values(&[source, result])
+        .inc();
+    status.last_result = result;
+    status.last_error = err.map(|err| format!("{err:#}"));
+
+    if let Some((candidates, ignored)) = counts {
+        GAUGE_SEED_SOURCE_CANDIDATES
+            .with_label_values(&[source])
+            .set(candidates as i64);
+        GAUGE_SEED_SOURCE_IGNORED
+            .with_label_values(&[source])
+            .set(ignored as i64);
+        status.candidates = candidates;
+        status.ignored = ignored;
+    }
+
+    if result == "success" {
+        GAUGE_SEED_SOURCE_LAST_SUCCESS
+            .with_label_values(&[source])
+            .set(now as i64);
+        status.last_success = Some(now);
+    }
+}
+
+pub fn seed_source_statuses() -> BTreeMap<String, SourceStatus> {
+    SOURCE_STATUSES.lock().unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_seed_source_fetch() {
+        let source = "test_record_seed_source_fetch";
+        record_seed_source_fetch(
+            source,
+            Duration::from_millis(10),
+            FetchOutcome::Success {
+                candidates: 3,
+                ignored: 1,
+            },
+        );
+        let err = eyre::eyre!("could not find section");
+        record_seed_source_fetch(
+            source,
+            Duration::from_millis(10),
+            FetchOutcome::Failure { err: &err },
+        );
+
+        let status = seed_source_statuses().remove(source).unwrap();
+        assert_eq!(status.last_result, "failure");
+        assert_eq!(status.last_error.as_deref(), Some("could not find section"));
+        assert!(status.last_success.is_some());
+        assert_eq!(status.candidates, 3);
+        assert_eq!(
+            COUNTER_SEED_SOURCE_FETCHES
+                .with_label_values(&[source, "failure"])
+                .get(),
+            1
+        );
+    }
+}
@@ -0,0 +1,326 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fs::{self, File},
+    io::ErrorKind,
+    net::Ipv4Addr,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use eyre::{Context, ContextCompat};
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+use tracing::{debug, warn};
+
+use crate::hl_gossip_config::HyperliquidSeedPeer;
+
+/// How many latency samples to keep per peer
+const MAX_LATENCY_SAMPLES: usize = 16;
+/// Consecutive failures after which peer gets temporarily banned
+const BAN_AFTER_CONSECUTIVE_FAILURES: u32 = 3;
+/// Ban duration, doubled for every consecutive failure past the threshold
+const BASE_BAN_DURATION: Duration = Duration::from_secs(30 * 60);
+const MAX_BAN_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
+/// Peers hl-node has been connected to within this window get ranked better
+const PROVEN_PEER_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+const PROVEN_PEER_SCORE_FACTOR: f64 = 0.75;
+/// Peers not seen for this long are dropped from the database
+const FORGET_AFTER: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// On-disk peer reputation database, keeping measurement history across restarts
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct PeerDb {
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(default)]
+    peers: BTreeMap<Ipv4Addr, PeerRecord>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PeerRecord {
+    /// Most recent latency samples in milliseconds, oldest first
+    pub latency_samples_ms: VecDeque<f64>,
+    pub successes: u32,
+    pub failures: u32,
+    pub consecutive_failures: u32,
+    /// Unix timestamps in seconds
+    pub last_seen: Option<u64>,
+    pub last_failure: Option<u64>,
+    /// Why the last measurement failed, e.g. "timed out" or "Connection refused (os error 111)"
+    pub last_failure_reason: Option<String>,
+    pub banned_until: Option<u64>,
+    /// Seed peer source labels this peer was obtained from
+    pub sources: BTreeSet<String>,
+    /// How many times peer was picked as a seed peer
+    pub times_selected: u32,
+    /// Established connections seen in hl-node gossip_connections logs during last scan
+    pub connections: u32,
+    pub last_connected: Option<u64>,
+}
+
+impl PeerRecord {
+    pub fn median_latency_ms(&self) -> Option<f64> {
+        if self.latency_samples_ms.is_empty() {
+            return None;
+        }
+
+        let mut samples: Vec<f64> = self.latency_samples_ms.iter().copied().collect();
+        samples.sort_by(f64::total_cmp);
+
+        let mid = samples.len() / 2;
+        Some(if samples.len().is_multiple_of(2) {
+            (samples[mid - 1] + samples[mid]) / 2.0
+        } else {
+            samples[mid]
+        })
+    }
+
+    fn failure_ratio(&self) -> f64 {
+        let total = self.successes + self.failures;
+        if total == 0 {
+            return 0.0;
+        }
+
+        self.failures as f64 / total as f64
+    }
+}
+
+impl PeerDb {
+    pub fn load<P: AsRef<Path>>(path: P) -> eyre::Result<Self> {
+        let path = path.as_ref();
+        let mut db = match File::open(path) {
+            Ok(file) => match serde_json::from_reader::<_, Self>(file) {
+                Ok(db) => db,
+                Err(err) => {
+                    warn!(
+                        ?err,
+                        ?path,
+                        "failed to parse peer database, starting from scratch"
+                    );
+                    Self::default()
+                }
+            },
+            Err(err) if err.kind() == ErrorKind::NotFound => Self::default(),
+            Err(err) => {
+                return Err(err)
+                    .wrap_err_with(|| format!("failed to open peer database at {path:?}"));
+            }
+        };
+
+        db.path = path.to_path_buf();
+        debug!(?path, peers = db.peers.len(), "loaded peer database");
+
+        Ok(db)
+    }
+
+    pub fn save(&mut self) -> eyre::Result<()> {
+        let now = unix_now();
+        self.peers.retain(|_, record| {
+            record
+                .last_seen
+                .max(record.last_failure)
+                .max(record.last_connected)
+                .is_some_and(|last| now.saturating_sub(last) < FORGET_AFTER.as_secs())
+        });
+
+        let directory = self
+            .path
+            .parent()
+            .wrap_err("failed to determine peer database directory")?;
+        fs::create_dir_all(directory)
+            .wrap_err_with(|| format!("failed to create peer database directory {directory:?}"))?;
+
+        let mut file = NamedTempFile::new_in(directory)?;
+        serde_json::to_writer(&mut file, self).wrap_err("failed to write peer database")?;
+        file.persist(&self.path)
+            .wrap_err("failed to replace peer database")?;
+
+        Ok(())
+    }
+
+    pub fn ips(&self) -> impl Iterator<Item = &Ipv4Addr> {
+        self.peers.keys()
+    }
+
+    pub fn get(&self, ip: &Ipv4Addr) -> Option<&PeerRecord> {
+        self.peers.get(ip)
+    }
+
+    pub fn is_banned(&self, ip: &Ipv4Addr) -> bool {
+        self.peers
+            .get(ip)
+            .and_then(|record| record.banned_until)
+            .is_some_and(|banned_until| banned_until > unix_now())
+    }
+
+    pub fn record_success(&mut self, peer: &HyperliquidSeedPeer, latency: Duration) {
+        let record = self.peers.entry(peer.ip).or_default();
+        record.sources.extend(peer.sources.iter().cloned());
+
+        if record.latency_samples_ms.len() >= MAX_LATENCY_SAMPLES {
+            record.latency_samples_ms.pop_front();
+        }
+        record
+            .latency_samples_ms
+            .push_back(latency.as_secs_f64() * 1e3);
+        record.successes += 1;
+        record.consecutive_failures = 0;
+        record.banned_until = None;
+        record.last_seen = Some(unix_now());
+    }
+
+    pub fn record_failure(&mut self, peer: &HyperliquidSeedPeer, reason: &str) {
+        let now = unix_now();
+        let record = self.peers.entry(peer.ip).or_default();
+        record.sources.extend(peer.sources.iter().cloned());
+
+        record.failures += 1;
+        record.consecutive_failures += 1;
+        record.last_failure = Some(now);
+        record.last_failure_reason = Some(reason.to_string());
+
+        if record.consecutive_failures >= BAN_AFTER_CONSECUTIVE_FAILURES {
+            let exponent = (record.consecutive_failures - BAN_AFTER_CONSECUTIVE_FAILURES).min(16);
+            let ban_duration = BASE_BAN_DURATION
+                .saturating_mul(1 << exponent)
+                .min(MAX_BAN_DURATION);
+
+            debug!(
+                ip = ?peer.ip,
+                consecutive_failures = record.consecutive_failures,
+                ?ban_duration,
+                "banning seed peer"
+            );
+            record.banned_until = Some(now + ban_duration.as_secs());
+        }
+    }
+
+    /// Records peer hl-node was observed to be connected to
+    pub fn record_connection(&mut self, ip: &Ipv4Addr, connections: u32, last_connected: u64) {
+        let record = self.peers.entry(*ip).or_default();
+        record.sources.insert("learned".to_string());
+        record.connections = connections;
+        record.last_connected = record.last_connected.max(Some(last_connected));
+    }
+
+    pub fn record_selected(&mut self, ip: &Ipv4Addr) {
+        self.peers.entry(*ip).or_default().times_selected += 1;
+    }
+
+    /// Effective latency used for ranking peers. Uses median of recorded samples to smooth out noisy one-off
+    /// measurements, penalizes peers which have been failing & prefers peers hl-node recently synced from.
+    pub fn score(&self, ip: &Ipv4Addr, latency: Duration) -> Duration {
+        let Some(record) = self.peers.get(ip) else {
+            return latency;
+        };
+
+        let latency_ms = record
+            .median_latency_ms()
+            .unwrap_or(latency.as_secs_f64() * 1e3);
+
+        let proven = record
+            .last_connected
+            .is_some_and(|last| unix_now().saturating_sub(last) < PROVEN_PEER_WINDOW.as_secs());
+        let factor = if proven {
+            PROVEN_PEER_SCORE_FACTOR
+        } else {
+            1.0
+        };
+
+        Duration::from_secs_f64(latency_ms * (1.0 + record.failure_ratio()) * factor / 1e3)
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn peer(ip: [u8; 4]) -> HyperliquidSeedPeer {
+        HyperliquidSeedPeer {
+            operator_name: "test".to_string(),
+            sources: vec!["test".to_string()],
+            ip: Ipv4Addr::from(ip),
+        }
+    }
+
+    #[test]
+    fn test_peer_db_bans_failing_peers() {
+        let mut db = PeerDb::default();
+        let peer = peer([1, 2, 3, 4]);
+
+        for _ in 0..BAN_AFTER_CONSECUTIVE_FAILURES - 1 {
+            db.record_failure(&peer, "timed out");
+        }
+        assert!(!db.is_banned(&peer.ip));
+
+        db.record_failure(&peer, "timed out");
+        assert!(db.is_banned(&peer.ip));
+
+        db.record_success(&peer, Duration::from_millis(10));
+        assert!(!db.is_banned(&peer.ip));
+    }
+
+    #[test]
+    fn test_peer_db_score_smooths_noise() {
+        let mut db = PeerDb::default();
+        let good = peer([1, 1, 1, 1]);
+        let flaky = peer([2, 2, 2, 2]);
+
+        for _ in 0..5 {
+            db.record_success(&good, Duration::from_millis(10));
+        }
+        // One-off slow measurement doesn't outweigh history
+        db.record_success(&good, Duration::from_millis(70));
+        assert_eq!(
+            db.score(&good.ip, Duration::from_millis(70)),
+            Duration::from_millis(10)
+        );
+
+        db.record_success(&flaky, Duration::from_millis(8));
+        db.record_failure(&flaky, "timed out");
+        assert!(
+            db.score(&flaky.ip, Duration::from_millis(8))
+                > db.score(&good.ip, Duration::from_millis(70))
+        );
+
+        // Peers node has been connected to are preferred
+        let proven = peer([4, 4, 4, 4]);
+        db.record_success(&proven, Duration::from_millis(12));
+        db.record_connection(&proven.ip, 10, unix_now());
+        assert!(db.score(&proven.ip, Duration::from_millis(12)) < Duration::from_millis(10));
+
+        // Unknown peers are ranked by the measurement alone
+        assert_eq!(
+            db.score(&Ipv4Addr::new(3, 3, 3, 3), Duration::from_millis(5)),
+            Duration::from_millis(5)
+        );
+    }
+
+    #[test]
+    fn test_peer_db_roundtrip() -> eyre::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("hl-bootstrap/peers.json");
+
+        let mut db = PeerDb::load(&path)?;
+        db.record_success(&peer([1, 2, 3, 4]), Duration::from_millis(12));
+        db.record_selected(&Ipv4Addr::new(1, 2, 3, 4));
+        db.save()?;
+
+        let db = PeerDb::load(&path)?;
+        let record = &db.peers[&Ipv4Addr::new(1, 2, 3, 4)];
+        assert_eq!(record.successes, 1);
+        assert_eq!(record.times_selected, 1);
+        assert!(record.sources.contains("test"));
+
+        Ok(())
+    }
+}
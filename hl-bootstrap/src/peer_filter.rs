@@ -0,0 +1,301 @@
+use std::{fmt, net::Ipv4Addr, str::FromStr};
+
+use eyre::{Context, bail};
+use tracing::debug;
+
+use crate::hl_gossip_config::HyperliquidSeedPeer;
+
+/// Ranges which are never reachable as public seed peers, see RFC 6890
+const BOGON_RANGES: &[Ipv4Cidr] = &[
+    Ipv4Cidr::new(Ipv4Addr::new(0, 0, 0, 0), 8),
+    Ipv4Cidr::new(Ipv4Addr::new(10, 0, 0, 0), 8),
+    Ipv4Cidr::new(Ipv4Addr::new(100, 64, 0, 0), 10),
+    Ipv4Cidr::new(Ipv4Addr::new(127, 0, 0, 0), 8),
+    Ipv4Cidr::new(Ipv4Addr::new(169, 254, 0, 0), 16),
+    Ipv4Cidr::new(Ipv4Addr::new(172, 16, 0, 0), 12),
+    Ipv4Cidr::new(Ipv4Addr::new(192, 0, 0, 0), 24),
+    Ipv4Cidr::new(Ipv4Addr::new(192, 0, 2, 0), 24),
+    Ipv4Cidr::new(Ipv4Addr::new(192, 168, 0, 0), 16),
+    Ipv4Cidr::new(Ipv4Addr::new(198, 18, 0, 0), 15),
+    Ipv4Cidr::new(Ipv4Addr::new(198, 51, 100, 0), 24),
+    Ipv4Cidr::new(Ipv4Addr::new(203, 0, 113, 0), 24),
+    Ipv4Cidr::new(Ipv4Addr::new(224, 0, 0, 0), 4),
+    Ipv4Cidr::new(Ipv4Addr::new(240, 0, 0, 0), 4),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ipv4Cidr {
+    network: Ipv4Addr,
+    prefix_len: u8,
+}
+
+impl Ipv4Cidr {
+    const fn new(network: Ipv4Addr, prefix_len: u8) -> Self {
+        Self {
+            network,
+            prefix_len,
+        }
+    }
+
+    fn mask(&self) -> u32 {
+        u32::MAX
+            .checked_shl(32 - self.prefix_len as u32)
+            .unwrap_or(0)
+    }
+
+    pub fn contains(&self, ip: &Ipv4Addr) -> bool {
+        u32::from(*ip) & self.mask() == u32::from(self.network) & self.mask()
+    }
+}
+
+impl FromStr for Ipv4Cidr {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (network, prefix_len) = match s.split_once('/') {
+            Some((network, prefix_len)) => (
+                network,
+                prefix_len
+                    .parse::<u8>()
+                    .wrap_err_with(|| format!("invalid prefix length in '{s}'"))?,
+            ),
+            None => (s, 32),
+        };
+        if prefix_len > 32 {
+            bail!("prefix length in '{s}' is larger than 32");
+        }
+
+        let network: Ipv4Addr = network
+            .parse()
+            .wrap_err_with(|| format!("invalid IPv4 address in '{s}'"))?;
+        let cidr = Self::new(network, prefix_len);
+        if u32::from(network) & !cidr.mask() != 0 {
+            bail!("'{s}' has host bits set");
+        }
+
+        Ok(cidr)
+    }
+}
+
+impl fmt::Display for Ipv4Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix_len)
+    }
+}
+
+/// Single `--seed-peers-ignored` entry: an IP, a CIDR block or an operator name pattern
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IgnoreRule {
+    Network(Ipv4Cidr),
+    /// Case-insensitive operator name pattern, `*` matches any sequence of characters
+    Operator(String),
+}
+
+impl IgnoreRule {
+    fn matches(&self, peer: &HyperliquidSeedPeer) -> bool {
+        match self {
+            Self::Network(cidr) => cidr.contains(&peer.ip),
+            Self::Operator(pattern) => glob_matches(pattern, &peer.operator_name.to_lowercase()),
+        }
+    }
+}
+
+impl FromStr for IgnoreRule {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(operator) = s.strip_prefix("operator:") {
+            return Ok(Self::Operator(operator.trim().to_lowercase()));
+        }
+
+        // Anything looking like an address has to be a valid one, so typos don't silently turn into operator names
+        if s.chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == '/')
+        {
+            return Ok(Self::Network(s.parse()?));
+        }
+
+        Ok(Self::Operator(s.to_lowercase()))
+    }
+}
+
+impl fmt::Display for IgnoreRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Network(cidr) => cidr.fmt(f),
+            Self::Operator(pattern) => write!(f, "operator:{pattern}"),
+        }
+    }
+}
+
+/// Matches `*` wildcards against the whole value
+fn glob_matches(pattern: &str, value: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let Some(first) = parts.next() else {
+        return value.is_empty();
+    };
+    let Some(mut rest) = value.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<_> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcards at all
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// Decides which seed peer candidates are never considered
+#[derive(Clone, Debug, Default)]
+pub struct PeerFilter {
+    pub rules: Vec<IgnoreRule>,
+    /// Whether to keep peers in private & other non-routable ranges
+    pub allow_private: bool,
+}
+
+impl PeerFilter {
+    pub fn new(rules: Vec<IgnoreRule>, allow_private: bool) -> Self {
+        Self {
+            rules,
+            allow_private,
+        }
+    }
+
+    pub fn is_ignored(&self, peer: &HyperliquidSeedPeer) -> bool {
+        if !self.allow_private && is_bogon(&peer.ip) {
+            debug!(operator_name = peer.operator_name, ip = ?peer.ip, "skipping non-routable seed node");
+            return true;
+        }
+
+        if let Some(rule) = self.rules.iter().find(|rule| rule.matches(peer)) {
+            debug!(operator_name = peer.operator_name, ip = ?peer.ip, %rule, "skipping ignored seed node");
+            return true;
+        }
+
+        false
+    }
+}
+
+/// Whether the address is in a private, reserved or otherwise non-routable range
+pub fn is_bogon(ip: &Ipv4Addr) -> bool {
+    ip.is_broadcast() || BOGON_RANGES.iter().any(|cidr| cidr.contains(ip))
+}
+
+/// `--seed-peers-extra` entry, `ip` or `ip=label`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtraSeedPeer {
+    pub ip: Ipv4Addr,
+    pub label: Option<String>,
+}
+
+impl FromStr for ExtraSeedPeer {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (ip, label) = match s.split_once('=') {
+            Some((ip, label)) => (ip, Some(label.trim().to_string())),
+            None => (s, None),
+        };
+
+        Ok(Self {
+            ip: ip
+                .trim()
+                .parse()
+                .wrap_err_with(|| format!("invalid extra seed peer IP in '{s}'"))?,
+            label: label.filter(|label| !label.is_empty()),
+        })
+    }
+}
+
+impl From<&ExtraSeedPeer> for HyperliquidSeedPeer {
+    fn from(value: &ExtraSeedPeer) -> Self {
+        Self {
+            operator_name: value.label.clone().unwrap_or_else(|| "manual".to_string()),
+            sources: vec!["args".to_string()],
+            ip: value.ip,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer(operator_name: &str, ip: [u8; 4]) -> HyperliquidSeedPeer {
+        HyperliquidSeedPeer {
+            operator_name: operator_name.to_string(),
+            sources: vec!["test".to_string()],
+            ip: Ipv4Addr::from(ip),
+        }
+    }
+
+    #[test]
+    fn test_parse_ignore_rules() -> eyre::Result<()> {
+        assert_eq!(
+            "1.2.3.4".parse::<IgnoreRule>()?,
+            IgnoreRule::Network(Ipv4Cidr::new(Ipv4Addr::new(1, 2, 3, 4), 32))
+        );
+        assert_eq!(
+            "1.2.0.0/16".parse::<IgnoreRule>()?,
+            IgnoreRule::Network(Ipv4Cidr::new(Ipv4Addr::new(1, 2, 0, 0), 16))
+        );
+        assert_eq!(
+            "Some Operator*".parse::<IgnoreRule>()?,
+            IgnoreRule::Operator("some operator*".to_string())
+        );
+        assert_eq!(
+            "operator:123".parse::<IgnoreRule>()?,
+            IgnoreRule::Operator("123".to_string())
+        );
+        assert!("1.2.3.4/33".parse::<IgnoreRule>().is_err());
+        assert!("1.2.3.4/16".parse::<IgnoreRule>().is_err());
+        assert!("1.2.3".parse::<IgnoreRule>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_peer_filter() -> eyre::Result<()> {
+        let filter = PeerFilter::new(
+            vec!["5.6.0.0/16".parse()?, "*cloud*".parse()?, "exact".parse()?],
+            false,
+        );
+
+        assert!(!filter.is_ignored(&peer("Validator", [1, 2, 3, 4])));
+        assert!(filter.is_ignored(&peer("Validator", [5, 6, 7, 8])));
+        assert!(filter.is_ignored(&peer("Some Cloud Provider", [1, 2, 3, 4])));
+        assert!(filter.is_ignored(&peer("EXACT", [1, 2, 3, 4])));
+        assert!(!filter.is_ignored(&peer("exactly", [1, 2, 3, 4])));
+        assert!(filter.is_ignored(&peer("Validator", [10, 0, 0, 1])));
+        assert!(filter.is_ignored(&peer("Validator", [100, 64, 1, 1])));
+
+        let filter = PeerFilter::new(Vec::new(), true);
+        assert!(!filter.is_ignored(&peer("Validator", [10, 0, 0, 1])));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_extra_seed_peer() -> eyre::Result<()> {
+        let extra: ExtraSeedPeer = "1.2.3.4=My Sentry".parse()?;
+        assert_eq!(extra.label.as_deref(), Some("My Sentry"));
+        assert_eq!(HyperliquidSeedPeer::from(&extra).operator_name, "My Sentry");
+
+        let extra: ExtraSeedPeer = "1.2.3.4".parse()?;
+        assert_eq!(HyperliquidSeedPeer::from(&extra).operator_name, "manual");
+
+        assert!("foo=bar".parse::<ExtraSeedPeer>().is_err());
+
+        Ok(())
+    }
+}
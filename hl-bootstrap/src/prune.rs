@@ -18,20 +18,25 @@ pub async fn prune_worker_task<P: AsRef<Path>>(
     interval.tick().await; // will complete immediately, as per interval API
 
     info!(?base_path, ?prune_older_than, "pruning node data directory");
-    if let Err(err) = run_cleanup(&base_path, prune_older_than).await {
+    if let Err(err) = run_cleanup(&base_path, prune_older_than, false).await {
         warn!(?err, "initial node data prune failed");
     }
 
     loop {
         interval.tick().await;
 
-        if let Err(err) = run_cleanup(&base_path, prune_older_than).await {
+        if let Err(err) = run_cleanup(&base_path, prune_older_than, false).await {
             warn!(?err, ?prune_older_than, "scheduled node data prune failed");
         }
     }
 }
 
-async fn run_cleanup<P: AsRef<Path>>(data_path: P, prune_older_than: Duration) -> eyre::Result<()> {
+/// Removes files older than `prune_older_than`, returning files picked for removal. Nothing is removed with `dry_run`
+pub async fn run_cleanup<P: AsRef<Path>>(
+    data_path: P,
+    prune_older_than: Duration,
+    dry_run: bool,
+) -> eyre::Result<Vec<PathBuf>> {
     let data_path = data_path.as_ref();
     let now = SystemTime::now();
 
@@ -47,11 +52,16 @@ async fn run_cleanup<P: AsRef<Path>>(data_path: P, prune_older_than: Duration) -
     )
     .await?;
 
+    if dry_run {
+        info!(files = files_to_remove.len(), "prune dry run complete");
+        return Ok(files_to_remove);
+    }
+
     let mut removed = 0_usize;
     let mut failed = 0_usize;
 
-    for file_path in files_to_remove {
-        match fs::remove_file(&file_path) {
+    for file_path in &files_to_remove {
+        match fs::remove_file(file_path) {
             Ok(()) => {
                 trace!(?file_path, "file removed");
                 removed += 1;
@@ -65,7 +75,7 @@ async fn run_cleanup<P: AsRef<Path>>(data_path: P, prune_older_than: Duration) -
 
     info!(removed, failed, "prune complete",);
 
-    Ok(())
+    Ok(files_to_remove)
 }
 
 async fn collect_files_recursive(
@@ -188,7 +198,7 @@ mod tests {
         fs::write(&new_file, "new content")?;
         set_file_mtime(&new_file, now - Duration::from_secs(1800))?; // 30 minutes ago
 
-        run_cleanup(&data_dir, cutoff).await?;
+        run_cleanup(&data_dir, cutoff, false).await?;
 
         // Old file should be removed
         assert!(!old_file.exists(), "Old file should be removed");
@@ -198,6 +208,24 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_prune_dry_run_keeps_files() -> eyre::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let data_dir = temp_dir.path().join("hl/data");
+
+        let old_file = data_dir.join("subdir/old_file.txt");
+        fs::create_dir_all(old_file.parent().unwrap())?;
+        fs::write(&old_file, "old content")?;
+        set_file_mtime(&old_file, SystemTime::now() - Duration::from_secs(7200))?;
+
+        let files = run_cleanup(&data_dir, Duration::from_secs(3600), true).await?;
+
+        assert_eq!(files, vec![old_file.clone()]);
+        assert!(old_file.exists(), "Dry run should not remove files");
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_prune_skips_base_directory_files() -> eyre::Result<()> {
         let temp_dir = TempDir::new()?;
@@ -212,7 +240,7 @@ mod tests {
         fs::write(&base_file, "base content")?;
         set_file_mtime(&base_file, now - Duration::from_secs(7200))?;
 
-        run_cleanup(&data_dir, cutoff).await?;
+        run_cleanup(&data_dir, cutoff, false).await?;
 
         // Base directory file should still exist
         assert!(
@@ -238,7 +266,7 @@ mod tests {
         fs::write(&stderr_file, "stderr content")?;
         set_file_mtime(&stderr_file, now - Duration::from_secs(7200))?;
 
-        run_cleanup(&data_dir, cutoff).await?;
+        run_cleanup(&data_dir, cutoff, false).await?;
 
         // visor_child_stderr should still exist
         assert!(
@@ -268,7 +296,7 @@ mod tests {
         fs::write(&nested_new, "nested new")?;
         set_file_mtime(&nested_new, now - Duration::from_secs(1800))?;
 
-        run_cleanup(&data_dir, cutoff).await?;
+        run_cleanup(&data_dir, cutoff, false).await?;
 
         assert!(!nested_old.exists(), "Nested old file should be removed");
         assert!(nested_new.exists(), "Nested new file should not be removed");
@@ -282,7 +310,7 @@ mod tests {
         let non_existent_dir = temp_dir.path().join("nonexistent/hl/data");
 
         // Should not panic or error on missing directory
-        let result = run_cleanup(&non_existent_dir, Duration::from_secs(3600)).await;
+        let result = run_cleanup(&non_existent_dir, Duration::from_secs(3600), false).await;
         // It should either succeed (if it handles gracefully) or return an error we can handle
         // The current implementation uses read_dir which will fail, but that's ok for this test
         assert!(result.is_ok() || result.is_err());
@@ -317,7 +345,7 @@ mod tests {
         fs::write(&new_file, "new content")?;
         set_file_mtime(&new_file, now - Duration::from_secs(1800))?;
 
-        run_cleanup(&data_dir, cutoff).await?;
+        run_cleanup(&data_dir, cutoff, false).await?;
 
         // All old files should be removed
         for file in &old_files {
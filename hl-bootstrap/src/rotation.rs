@@ -0,0 +1,204 @@
+use std::{collections::HashSet, net::Ipv4Addr, time::Duration};
+
+use eyre::bail;
+use tokio::{
+    sync::mpsc,
+    time::{Instant, MissedTickBehavior, interval},
+};
+use tracing::{debug, info, warn};
+
+use crate::{
+    child::ChildControl,
+    hl_gossip_config::HyperliquidSeedPeer,
+    monitor::node_drift,
+    peer_db::PeerDb,
+    seed_peers::{
+        SeedPeersOptions, discover_seed_peers, read_existing_gossip_config, write_gossip_config,
+    },
+    selection::select_seed_peers,
+    speedtest::{measure_seed_peers, record_picked_seed_peers},
+};
+
+/// How often node drift is checked
+const DRIFT_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Debug)]
+pub struct RotationOptions {
+    pub interval: Duration,
+    /// Relative score improvement over current seed peers required to rotate them
+    pub min_improvement: f64,
+    /// Seed peers get rotated when node drift stays above this for `drift_grace`
+    pub drift_threshold: Duration,
+    pub drift_grace: Duration,
+}
+
+/// Worker task that periodically re-measures seed peer candidates & rewrites override_gossip_config.json when the
+/// current seed peers are materially worse than the alternatives, or when the node keeps drifting behind
+pub async fn rotation_worker_task(
+    seed_peers: SeedPeersOptions,
+    options: RotationOptions,
+    control: Option<mpsc::Sender<ChildControl>>,
+) {
+    let mut rotation_interval = interval(options.interval);
+    rotation_interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    rotation_interval.tick().await; // will complete immediately, seed peers were just picked during setup
+
+    let mut drift_interval = interval(DRIFT_CHECK_INTERVAL);
+    drift_interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    let mut drifting_since: Option<Instant> = None;
+
+    loop {
+        let drifting = tokio::select! {
+            _ = rotation_interval.tick() => false,
+            _ = drift_interval.tick() => {
+                match node_drift() {
+                    Some(drift) if drift > options.drift_threshold => {
+                        let since = *drifting_since.get_or_insert_with(Instant::now);
+                        if since.elapsed() < options.drift_grace {
+                            continue;
+                        }
+
+                        warn!(?drift, drifting_for = ?since.elapsed(), "node keeps drifting, rotating seed peers");
+                        true
+                    }
+                    _ => {
+                        drifting_since = None;
+                        continue;
+                    }
+                }
+            }
+        };
+
+        let min_improvement = (!drifting).then_some(options.min_improvement);
+        match rotate_seed_peers(&seed_peers, min_improvement).await {
+            Ok(true) => {
+                if let Some(control) = &control
+                    && let Err(err) = control
+                        .send(ChildControl::Restart {
+                            reason: "seed peers rotated",
+                        })
+                        .await
+                {
+                    warn!(?err, "failed to request hl-visor restart");
+                }
+            }
+            Ok(false) => {}
+            Err(err) => warn!(?err, "seed peer rotation failed"),
+        }
+
+        // Give the node (and new seed peers) time before acting on the drift again
+        if drifting {
+            drifting_since = None;
+        }
+    }
+}
+
+/// Re-picks seed peers when they're at least `min_improvement` better than the current ones, or whenever they differ
+/// without `min_improvement`. Returns whether override_gossip_config.json changed, which it might not after merging
+/// with the existing one, e.g. with `union` or `keep` root_node_ips merge policy
+pub async fn rotate_seed_peers(
+    seed_peers: &SeedPeersOptions,
+    min_improvement: Option<f64>,
+) -> eyre::Result<bool> {
+    let options = seed_peers.speedtest;
+
+    let current_ips: Vec<Ipv4Addr> = read_existing_gossip_config(seed_peers)?
+        .map(|config| config.root_node_ips.iter().map(|node| node.ip).collect())
+        .unwrap_or_default();
+
+    let mut peer_db = PeerDb::load(&seed_peers.peer_db_path)?;
+
+    // Current seed peers are measured as well, even if sources no longer list them
+    let mut candidates = discover_seed_peers(seed_peers, &mut peer_db).await?;
+    let peer_filter = &seed_peers.peer_filter;
+    for ip in &current_ips {
+        let current = HyperliquidSeedPeer {
+            operator_name: "current".to_string(),
+            sources: vec!["override_gossip_config.json".to_string()],
+            ip: *ip,
+        };
+        if !candidates.iter().any(|candidate| candidate.ip == *ip)
+            && !peer_filter.is_ignored(&current)
+        {
+            candidates.push(current);
+        }
+    }
+
+    let measurements = measure_seed_peers(candidates, options, &mut peer_db).await?;
+
+    // Unreachable & too slow current seed peers count as twice the latency threshold
+    let current_score = mean_score(current_ips.iter().map(|ip| {
+        measurements
+            .iter()
+            .find(|measurement| measurement.peer.ip == *ip)
+            .map(|measurement| measurement.score)
+            .unwrap_or(options.max_latency * 2)
+    }));
+    let picked = select_seed_peers(&measurements, seed_peers.amount, seed_peers.selection);
+    let best_score = mean_score(picked.iter().map(|measurement| measurement.score));
+
+    let current_set: HashSet<_> = current_ips.iter().collect();
+    let best_set: HashSet<_> = picked
+        .iter()
+        .map(|measurement| &measurement.peer.ip)
+        .collect();
+
+    let improvement = match (current_score, best_score) {
+        (Some(current), Some(best)) => 1.0 - best.as_secs_f64() / current.as_secs_f64(),
+        (None, Some(_)) => f64::INFINITY,
+        (_, None) => f64::NEG_INFINITY,
+    };
+
+    let force = min_improvement.is_none();
+    debug!(
+        ?current_score,
+        ?best_score,
+        improvement,
+        force,
+        "seed peer rotation evaluation"
+    );
+
+    let rotate = best_score.is_some()
+        && current_set != best_set
+        && min_improvement.is_none_or(|min_improvement| improvement >= min_improvement);
+
+    if !rotate {
+        if let Err(err) = peer_db.save() {
+            warn!(?err, "failed to save peer database");
+        }
+        return Ok(false);
+    }
+
+    info!(
+        ?current_score,
+        ?best_score,
+        improvement,
+        force,
+        "rotating seed peers"
+    );
+    record_picked_seed_peers(&picked, &mut peer_db);
+    if let Err(err) = peer_db.save() {
+        warn!(?err, "failed to save peer database");
+    }
+
+    if picked.is_empty() {
+        bail!("no seed nodes passed latency threshold");
+    }
+
+    let changed = write_gossip_config(seed_peers, &picked)?;
+    if !changed {
+        info!(
+            "gossip config is unchanged after merging with existing one, not restarting hl-visor"
+        );
+    }
+
+    Ok(changed)
+}
+
+fn mean_score(scores: impl Iterator<Item = Duration>) -> Option<Duration> {
+    let (sum, count) = scores.fold((Duration::ZERO, 0u32), |(sum, count), score| {
+        (sum + score, count + 1)
+    });
+
+    (count > 0).then(|| sum / count)
+}
@@ -0,0 +1,154 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use eyre::{Context, ContextCompat};
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+use tracing::{debug, warn};
+
+use crate::peer_db::unix_now;
+
+/// Last-known-good raw seed source responses, used for conditional requests and as a fallback when sources are
+/// unreachable
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct SeedCache {
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(skip)]
+    max_staleness: Duration,
+    #[serde(default)]
+    entries: BTreeMap<String, SeedCacheEntry>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SeedCacheEntry {
+    /// Unix timestamp in seconds of when the content was last fetched or revalidated
+    pub fetched_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+    pub content: String,
+}
+
+impl SeedCacheEntry {
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(unix_now().saturating_sub(self.fetched_at))
+    }
+}
+
+impl SeedCache {
+    pub fn load<P: AsRef<Path>>(path: P, max_staleness: Duration) -> eyre::Result<Self> {
+        let path = path.as_ref();
+        let mut cache = match File::open(path) {
+            Ok(file) => match serde_json::from_reader::<_, Self>(file) {
+                Ok(cache) => cache,
+                Err(err) => {
+                    warn!(
+                        ?err,
+                        ?path,
+                        "failed to parse seed cache, starting from scratch"
+                    );
+                    Self::default()
+                }
+            },
+            Err(err) if err.kind() == ErrorKind::NotFound => Self::default(),
+            Err(err) => {
+                return Err(err).wrap_err_with(|| format!("failed to open seed cache at {path:?}"));
+            }
+        };
+
+        cache.path = path.to_path_buf();
+        cache.max_staleness = max_staleness;
+        debug!(?path, entries = cache.entries.len(), "loaded seed cache");
+
+        Ok(cache)
+    }
+
+    pub fn save(&self) -> eyre::Result<()> {
+        let directory = self
+            .path
+            .parent()
+            .wrap_err("failed to determine seed cache directory")?;
+        fs::create_dir_all(directory)
+            .wrap_err_with(|| format!("failed to create seed cache directory {directory:?}"))?;
+
+        let mut file = NamedTempFile::new_in(directory)?;
+        serde_json::to_writer(&mut file, self).wrap_err("failed to write seed cache")?;
+        file.persist(&self.path)
+            .wrap_err("failed to replace seed cache")?;
+
+        Ok(())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&SeedCacheEntry> {
+        self.entries.get(key)
+    }
+
+    /// Cached entry, if it is not older than the configured max staleness
+    pub fn get_fresh(&self, key: &str) -> Option<&SeedCacheEntry> {
+        self.entries
+            .get(key)
+            .filter(|entry| entry.age() <= self.max_staleness)
+    }
+
+    pub fn insert(
+        &mut self,
+        key: String,
+        etag: Option<String>,
+        last_modified: Option<String>,
+        content: String,
+    ) {
+        self.entries.insert(
+            key,
+            SeedCacheEntry {
+                fetched_at: unix_now(),
+                etag,
+                last_modified,
+                content,
+            },
+        );
+    }
+
+    /// Marks cached content as revalidated by the source
+    pub fn touch(&mut self, key: &str) {
+        if let Some(entry) = self.entries.get_mut(key) {
+            entry.fetched_at = unix_now();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_seed_cache_staleness() -> eyre::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("seed_cache.json");
+
+        let mut cache = SeedCache::load(&path, Duration::from_secs(3600))?;
+        cache.insert("fresh".to_string(), None, None, "1.2.3.4".to_string());
+        cache.insert(
+            "stale".to_string(),
+            Some("\"abc\"".to_string()),
+            None,
+            "5.6.7.8".to_string(),
+        );
+        cache.entries.get_mut("stale").unwrap().fetched_at -= 7200;
+        cache.save()?;
+
+        let cache = SeedCache::load(&path, Duration::from_secs(3600))?;
+        assert!(cache.get_fresh("fresh").is_some());
+        assert!(cache.get_fresh("stale").is_none());
+        assert_eq!(cache.get("stale").unwrap().etag.as_deref(), Some("\"abc\""));
+
+        Ok(())
+    }
+}
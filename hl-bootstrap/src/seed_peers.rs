@@ -0,0 +1,345 @@
+use std::{net::Ipv4Addr, path::PathBuf, time::Duration};
+
+use eyre::{Context, ContextCompat, bail};
+use tempfile::NamedTempFile;
+use tracing::{debug, info, warn};
+
+use crate::{
+    gossip_diff::GossipConfigDiff,
+    gossip_logs::learn_gossip_peers,
+    gossip_validation::check_gossip_config,
+    hl_gossip_config::{
+        HyperliquidChain, HyperliquidSeedPeer, MergePolicies, MergePolicy, NodeIp, NodeRole,
+        OverrideGossipConfig, fetch_hyperliquid_seed_peers, load_override_gossip_config,
+        merge_seed_peers, read_override_gossip_config,
+    },
+    peer_db::PeerDb,
+    peer_filter::{ExtraSeedPeer, PeerFilter},
+    seed_cache::SeedCache,
+    seed_source::SeedPeerSource,
+    selection::SelectionStrategy,
+    speedtest::{SeedPeerMeasurement, SpeedtestOptions, speedtest_nodes},
+};
+
+/// Everything needed to discover, measure & pick seed peers, and to persist them into override_gossip_config.json
+#[derive(Clone, Debug)]
+pub struct SeedPeersOptions {
+    pub network: HyperliquidChain,
+    /// Chain in visor.json hl-visor is going to use, when it can be read
+    pub visor_chain: Option<HyperliquidChain>,
+    pub sources: Vec<SeedPeerSource>,
+    pub peer_filter: PeerFilter,
+    /// Minimum amount of distinct sources reporting a peer
+    pub quorum: usize,
+    pub extra: Vec<ExtraSeedPeer>,
+    /// How many peers learned from gossip_connections logs to consider, 0 disables
+    pub learned_limit: usize,
+    pub learned_max_age: Duration,
+    pub data_directory: PathBuf,
+    pub peer_db_path: PathBuf,
+    pub seed_cache_path: PathBuf,
+    pub seed_cache_max_staleness: Duration,
+    /// How many seed peers to pick
+    pub amount: usize,
+    pub selection: SelectionStrategy,
+    pub speedtest: SpeedtestOptions,
+    pub gossip_config_path: PathBuf,
+    pub node_role: NodeRole,
+    pub reserved_peers: Vec<Ipv4Addr>,
+    pub merge_policies: MergePolicies,
+    /// Move unparseable override_gossip_config.json aside instead of failing
+    pub replace_invalid_gossip_config: bool,
+    /// Leave peer database, seed cache & existing override_gossip_config.json untouched, for diagnostic commands
+    pub dry_run: bool,
+}
+
+/// Fetches seed peer candidates from all configured sources, including extra seed peers & peers learned from hl-node
+/// logs
+pub async fn discover_seed_peers(
+    options: &SeedPeersOptions,
+    peer_db: &mut PeerDb,
+) -> eyre::Result<Vec<HyperliquidSeedPeer>> {
+    let network = &options.network;
+    let peer_filter = &options.peer_filter;
+
+    // Custom chains may have no seed sources & rely on extra or learned peers only
+    let mut seed_nodes = if options.sources.is_empty() {
+        info!(
+            ?network,
+            "no seed peer sources configured, not fetching seed nodes"
+        );
+        Vec::new()
+    } else {
+        info!(
+            ?network,
+            ?peer_filter,
+            quorum = options.quorum,
+            sources = ?options.sources.iter().map(|source| &source.label).collect::<Vec<_>>(),
+            "fetching seed nodes"
+        );
+        if options.quorum > options.sources.len() {
+            warn!(
+                quorum = options.quorum,
+                sources = options.sources.len(),
+                "seed peers quorum is larger than the number of seed sources"
+            );
+        }
+        let mut seed_cache =
+            SeedCache::load(&options.seed_cache_path, options.seed_cache_max_staleness)?;
+        let seed_nodes = fetch_hyperliquid_seed_peers(
+            &options.sources,
+            peer_filter,
+            &mut seed_cache,
+            options.quorum,
+        )
+        .await;
+        if !options.dry_run
+            && let Err(err) = seed_cache.save()
+        {
+            warn!(?err, "failed to save seed cache");
+        }
+        let seed_nodes = seed_nodes?;
+        info!(?network, count = seed_nodes.len(), "got seed nodes");
+        seed_nodes
+    };
+
+    if !options.extra.is_empty() {
+        info!(
+            ?network,
+            count = options.extra.len(),
+            "including extra seed peers from args"
+        );
+        // Extra seed peers are explicitly asked for, so they are not filtered, only merged into fetched peers with
+        // the same IP
+        seed_nodes = merge_seed_peers(
+            seed_nodes
+                .into_iter()
+                .chain(options.extra.iter().map(HyperliquidSeedPeer::from)),
+        );
+    }
+
+    if options.learned_limit > 0 {
+        let learned = learn_gossip_peers(&options.data_directory, options.learned_max_age);
+        for peer in &learned {
+            peer_db.record_connection(&peer.ip, peer.observations, peer.last_seen);
+        }
+
+        let learned: Vec<_> = learned
+            .into_iter()
+            .filter(|peer| !seed_nodes.iter().any(|seed| seed.ip == peer.ip))
+            .map(|peer| HyperliquidSeedPeer {
+                operator_name: "learned".to_string(),
+                sources: vec!["gossip_connections".to_string()],
+                ip: peer.ip,
+            })
+            .filter(|peer| !peer_filter.is_ignored(peer))
+            .take(options.learned_limit)
+            .collect();
+        if !learned.is_empty() {
+            info!(
+                ?network,
+                count = learned.len(),
+                "including seed peers learned from gossip connections logs"
+            );
+        }
+        seed_nodes.extend(learned);
+    }
+
+    if options.sources.is_empty() && seed_nodes.is_empty() {
+        bail!(
+            "no seed peer sources configured for chain {}; add seed_sources in the chain config or pass extra seed \
+             peers",
+            network.to_string()
+        );
+    }
+
+    Ok(seed_nodes)
+}
+
+/// Discovers, measures & picks seed peers, keeping peer database up to date
+pub async fn pick_gossip_seed_peers(
+    options: &SeedPeersOptions,
+) -> eyre::Result<Vec<SeedPeerMeasurement>> {
+    let mut peer_db = PeerDb::load(&options.peer_db_path)?;
+    let seed_nodes = discover_seed_peers(options, &mut peer_db).await?;
+    if seed_nodes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let tested_seed_nodes = speedtest_nodes(
+        seed_nodes,
+        options.amount,
+        options.selection,
+        options.speedtest,
+        &mut peer_db,
+    )
+    .await
+    .wrap_err("failed to measure latency of seed nodes")?;
+
+    if !options.dry_run
+        && let Err(err) = peer_db.save()
+    {
+        warn!(?err, "failed to save peer database");
+    }
+
+    if tested_seed_nodes.is_empty() {
+        bail!(
+            "no seed nodes passed latency threshold (current: {:?}) and gossip probe (current: {:?}), try increasing \
+             threshold",
+            options.speedtest.max_latency,
+            options.speedtest.probe
+        );
+    }
+
+    Ok(tested_seed_nodes)
+}
+
+/// Reads existing override_gossip_config.json. Unparseable files are an error, unless they are to be replaced outside
+/// of a dry run
+pub fn read_existing_gossip_config(
+    options: &SeedPeersOptions,
+) -> eyre::Result<Option<OverrideGossipConfig>> {
+    if options.replace_invalid_gossip_config && !options.dry_run {
+        load_override_gossip_config(&options.gossip_config_path)
+    } else {
+        read_override_gossip_config(&options.gossip_config_path)
+    }
+}
+
+/// Merges configuration with existing override_gossip_config.json, returning the existing one along with the result
+pub fn merge_existing_gossip_config(
+    options: &SeedPeersOptions,
+    config: OverrideGossipConfig,
+    policies: &MergePolicies,
+) -> eyre::Result<(Option<OverrideGossipConfig>, OverrideGossipConfig)> {
+    let existing = read_existing_gossip_config(options)?;
+    let merged = match &existing {
+        Some(existing) => {
+            debug!(
+                gossip_config_path = ?options.gossip_config_path,
+                "merging with existing gossip config"
+            );
+            existing.clone().merge(config, policies)
+        }
+        None => config,
+    };
+
+    Ok((existing, merged))
+}
+
+/// Merges picked seed peers into override_gossip_config.json & atomically replaces it. Returns whether the merged
+/// configuration differs from the existing one
+pub fn write_gossip_config(
+    options: &SeedPeersOptions,
+    seed_nodes: &[SeedPeerMeasurement],
+) -> eyre::Result<bool> {
+    let (config, policies) = seed_peers_gossip_config(
+        options,
+        seed_nodes
+            .iter()
+            .map(|measurement| measurement.peer.clone()),
+    );
+    persist_gossip_config(options, config, &policies, seed_nodes)
+}
+
+/// Gossip configuration for picked seed peers, along with how to merge it into existing one
+pub fn seed_peers_gossip_config(
+    options: &SeedPeersOptions,
+    seed_nodes: impl IntoIterator<Item = HyperliquidSeedPeer>,
+) -> (OverrideGossipConfig, MergePolicies) {
+    let mut config = OverrideGossipConfig::new(options.network.clone());
+    for seed in seed_nodes {
+        config.root_node_ips.push(seed.into());
+    }
+    config.reserved_peer_ips = options.reserved_peers.clone();
+
+    // Adjust n_gossip_peers
+    // Allowed range is [1, 100]
+    // See https://github.com/hyperliquid-dex/node/blob/main/README_misc.md#additional-configuration
+    let n_gossip_peers = config.root_node_ips.len();
+    if n_gossip_peers > 8 {
+        config.n_gossip_peers = Some(n_gossip_peers.min(100) as u16);
+    }
+
+    let mut policies = options.merge_policies.clone();
+    // Sentries have to look for peers on behalf of the private nodes
+    if options.node_role == NodeRole::Sentry {
+        policies.try_new_peers = MergePolicy::Override;
+    }
+
+    (config, policies)
+}
+
+pub fn write_private_gossip_config(
+    options: &SeedPeersOptions,
+    sentry_ips: &[Ipv4Addr],
+) -> eyre::Result<()> {
+    info!(?sentry_ips, "setting up gossip through sentry nodes");
+
+    let (config, policies) = private_gossip_config(options, sentry_ips);
+    persist_gossip_config(options, config, &policies, &[])?;
+
+    Ok(())
+}
+
+/// Nodes behind sentries (private nodes & validators) only gossip through the sentries & don't look for other peers
+pub fn private_gossip_config(
+    options: &SeedPeersOptions,
+    sentry_ips: &[Ipv4Addr],
+) -> (OverrideGossipConfig, MergePolicies) {
+    let mut config = OverrideGossipConfig::new(options.network.clone());
+    config.try_new_peers = false;
+    config.root_node_ips = sentry_ips.iter().map(|ip| NodeIp { ip: *ip }).collect();
+    config.reserved_peer_ips = sentry_ips.to_vec();
+
+    (
+        config,
+        MergePolicies {
+            root_node_ips: MergePolicy::Override,
+            try_new_peers: MergePolicy::Override,
+            n_gossip_peers: options.merge_policies.n_gossip_peers,
+            reserved_peer_ips: MergePolicy::Override,
+        },
+    )
+}
+
+/// Merges configuration into existing override_gossip_config.json & atomically replaces it, logging what changed.
+/// The file is rewritten even when nothing changed, as its age tells when seed peers were last picked. Returns whether
+/// the merged configuration differs from the existing one.
+fn persist_gossip_config(
+    options: &SeedPeersOptions,
+    config: OverrideGossipConfig,
+    policies: &MergePolicies,
+    seed_nodes: &[SeedPeerMeasurement],
+) -> eyre::Result<bool> {
+    let config_path_directory = options
+        .gossip_config_path
+        .parent()
+        .wrap_err("failed to determine override_gossip_config.json directory")?;
+
+    let (existing, config) = merge_existing_gossip_config(options, config, policies)?;
+    check_gossip_config(&config, options.visor_chain.as_ref())?;
+    let changed = match &existing {
+        Some(existing) => serde_json::to_value(existing)? != serde_json::to_value(&config)?,
+        None => true,
+    };
+    match PeerDb::load(&options.peer_db_path) {
+        Ok(peer_db) => {
+            GossipConfigDiff::new(existing.as_ref(), &config, seed_nodes, &peer_db).log()
+        }
+        Err(err) => warn!(
+            ?err,
+            "failed to load peer database, not logging gossip config diff"
+        ),
+    }
+
+    let mut new_config_file = NamedTempFile::new_in(config_path_directory)?;
+    serde_json::to_writer(&mut new_config_file, &config)
+        .wrap_err("failed to write new configuration")?;
+
+    new_config_file
+        .persist(&options.gossip_config_path)
+        .wrap_err("failed to replace override_gossip_config.json")?;
+
+    Ok(changed)
+}
@@ -0,0 +1,758 @@
+use std::{
+    fs,
+    io::Write,
+    net::Ipv4Addr,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use eyre::{Context, ContextCompat, bail};
+use reqwest::{
+    Client, StatusCode,
+    header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use tempfile::NamedTempFile;
+use tracing::{debug, warn};
+
+use crate::{
+    gpg::verify_detached_signature,
+    hl_gossip_config::{HyperliquidChain, HyperliquidSeedPeer, OverrideGossipConfig},
+    monitor::sources::{FetchOutcome, record_seed_source_fetch},
+    peer_filter::PeerFilter,
+    seed_cache::{SeedCache, SeedCacheEntry},
+};
+
+structstruck::strike! {
+    #[structstruck::each[derive(Clone, Debug, Deserialize, Serialize)]]
+    pub struct SeedPeerSource {
+        /// Operator label, used as the peer operator name when the format does not carry one
+        pub label: String,
+        #[serde(flatten)]
+        pub location: pub enum SeedPeerSourceLocation {
+            #[serde(rename = "url")]
+            Url(String),
+            #[serde(rename = "path")]
+            Path(PathBuf),
+        },
+        pub format: pub enum SeedPeerSourceFormat {
+            #![derive(Copy, PartialEq, Eq)]
+            #![serde(rename_all = "snake_case")]
+
+            /// `["1.2.3.4", ...]`
+            JsonIpArray,
+            /// `{"root_node_ips": [{"Ip": "1.2.3.4"}], ...}`
+            OverrideGossipConfig,
+            /// Markdown table under `markdown_section` heading, with operator name & IP columns
+            MarkdownTable,
+            /// `operator_name,ip` or `ip` lines
+            Csv,
+            /// IP per line, `#` starts a comment
+            PlainText,
+        },
+        /// JSON body to POST to the URL, GET request is done when unset
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub request_body: Option<Value>,
+        /// Heading of the section containing the seed peers table, for markdown_table format
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub markdown_section: Option<String>,
+        /// Pinned hex SHA-256 checksum of the content, for sources which are not supposed to change
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub sha256: Option<String>,
+        /// URL of a detached gpg signature of the content, verified using current user's keyring
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub signature_url: Option<String>,
+    }
+}
+
+/// Seed peer sources hl-bootstrap knows about out of the box
+pub fn builtin_seed_sources(chain: &HyperliquidChain) -> Vec<SeedPeerSource> {
+    match chain {
+        HyperliquidChain::Mainnet => vec![
+            SeedPeerSource {
+                label: "Hyperliquid API-provided IP".to_string(),
+                location: SeedPeerSourceLocation::Url(
+                    "https://api.hyperliquid.xyz/info".to_string(),
+                ),
+                format: SeedPeerSourceFormat::JsonIpArray,
+                request_body: Some(json!({"type": "gossipRootIps"})),
+                markdown_section: None,
+                sha256: None,
+                signature_url: None,
+            },
+            // There is an API request to fetch mainnet non-validating seed node IPs since 2025-09-02, but it'll only give us
+            // JP IP addresses, which are usually unsuitable for syncing the node from EU.
+            // Keep Markdown table parsing code around for now.
+            SeedPeerSource {
+                label: "hyperliquid-dex/node README.md".to_string(),
+                location: SeedPeerSourceLocation::Url(
+                    "https://github.com/hyperliquid-dex/node/raw/refs/heads/main/README.md"
+                        .to_string(),
+                ),
+                format: SeedPeerSourceFormat::MarkdownTable,
+                request_body: None,
+                markdown_section: Some("Mainnet Non-Validator Seed Peers".to_string()),
+                sha256: None,
+                signature_url: None,
+            },
+        ],
+        // Imperator.co is generous
+        HyperliquidChain::Testnet => vec![SeedPeerSource {
+            label: "Imperator.co".to_string(),
+            location: SeedPeerSourceLocation::Url(
+                "https://hyperliquid-testnet.imperator.co/peers.json".to_string(),
+            ),
+            format: SeedPeerSourceFormat::OverrideGossipConfig,
+            request_body: None,
+            markdown_section: None,
+            sha256: None,
+            signature_url: None,
+        }],
+        // Custom chains only have sources from chain config
+        HyperliquidChain::Custom(_) => Vec::new(),
+    }
+}
+
+enum UrlResponse {
+    Modified {
+        content: String,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    },
+    NotModified,
+}
+
+impl SeedPeerSource {
+    /// Fetches peers from the source, recording the outcome in per-source metrics
+    pub async fn fetch(
+        &self,
+        client: &Client,
+        peer_filter: &PeerFilter,
+        cache: &mut SeedCache,
+    ) -> eyre::Result<Vec<HyperliquidSeedPeer>> {
+        let started = Instant::now();
+        let result = match &self.location {
+            SeedPeerSourceLocation::Url(url) => self.fetch_url(client, url, cache).await,
+            SeedPeerSourceLocation::Path(path) => self
+                .read_path(client, path)
+                .await
+                .map(|peers| (peers, None)),
+        };
+
+        let (peers, fallback_err) = match result {
+            Ok(result) => result,
+            Err(err) => {
+                record_seed_source_fetch(
+                    &self.label,
+                    started.elapsed(),
+                    FetchOutcome::Failure { err: &err },
+                );
+                return Err(err);
+            }
+        };
+
+        let (ignored, peers): (Vec<_>, Vec<_>) = peers
+            .into_iter()
+            .partition(|peer| peer_filter.is_ignored(peer));
+        let (candidates, ignored) = (peers.len(), ignored.len());
+        record_seed_source_fetch(
+            &self.label,
+            started.elapsed(),
+            match &fallback_err {
+                Some(err) => FetchOutcome::Cached {
+                    candidates,
+                    ignored,
+                    err,
+                },
+                None => FetchOutcome::Success {
+                    candidates,
+                    ignored,
+                },
+            },
+        );
+
+        Ok(peers)
+    }
+
+    async fn read_path(
+        &self,
+        client: &Client,
+        path: &Path,
+    ) -> eyre::Result<Vec<HyperliquidSeedPeer>> {
+        let content = fs::read_to_string(path)
+            .wrap_err_with(|| format!("failed to read seed peers from {path:?}"))?;
+        self.verify(client, &content).await?;
+        self.parse(&content)
+    }
+
+    /// Fetches & parses URL source, only caching content which parsed successfully. Falls back to the cached
+    /// content when the source is unreachable or returns garbage, returning the error along with the cached peers.
+    async fn fetch_url(
+        &self,
+        client: &Client,
+        url: &str,
+        cache: &mut SeedCache,
+    ) -> eyre::Result<(Vec<HyperliquidSeedPeer>, Option<eyre::Report>)> {
+        let cache_key = self.cache_key(url);
+
+        let result = match self.request(client, url, cache.get(&cache_key)).await {
+            Ok(UrlResponse::Modified {
+                content,
+                etag,
+                last_modified,
+            }) => match self.verify(client, &content).await {
+                // Cached content is always verified, so it stays usable as the last known good copy
+                Ok(()) => self.parse(&content).inspect(|_| {
+                    cache.insert(cache_key.clone(), etag, last_modified, content);
+                }),
+                Err(err) => Err(err),
+            },
+            Ok(UrlResponse::NotModified) => {
+                debug!(
+                    source = self.label,
+                    "seed source not modified, using cached content"
+                );
+                cache.touch(&cache_key);
+                let entry = cache
+                    .get(&cache_key)
+                    .wrap_err("seed source returned not modified, but nothing is cached")?;
+                self.parse_cached(client, &entry.content).await
+            }
+            Err(err) => Err(err),
+        };
+
+        let err = match result {
+            Ok(peers) => return Ok((peers, None)),
+            Err(err) => err,
+        };
+
+        let Some(entry) = cache.get_fresh(&cache_key) else {
+            return Err(err);
+        };
+        match self.parse_cached(client, &entry.content).await {
+            Ok(peers) => {
+                warn!(
+                    ?err,
+                    source = self.label,
+                    age = ?entry.age(),
+                    "failed to fetch seed peers, using last known good copy"
+                );
+                Ok((peers, Some(err)))
+            }
+            // Unusable cached content is no better than none
+            Err(cache_err) => {
+                warn!(
+                    ?cache_err,
+                    source = self.label,
+                    "cached seed peers are unusable, ignoring them"
+                );
+                Err(err)
+            }
+        }
+    }
+
+    /// Verifies cached content again before parsing it, as the pinned checksum or signature may have changed since it
+    /// was cached
+    async fn parse_cached(
+        &self,
+        client: &Client,
+        content: &str,
+    ) -> eyre::Result<Vec<HyperliquidSeedPeer>> {
+        self.verify(client, content).await.wrap_err_with(|| {
+            format!("cached seed peers from {} failed verification", self.label)
+        })?;
+        self.parse(content)
+    }
+
+    async fn request(
+        &self,
+        client: &Client,
+        url: &str,
+        cached: Option<&SeedCacheEntry>,
+    ) -> eyre::Result<UrlResponse> {
+        let mut request = match &self.request_body {
+            Some(body) => client.post(url).json(body),
+            None => client.get(url),
+        };
+
+        if let Some(cached) = cached {
+            if let Some(etag) = &cached.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = request
+            .send()
+            .await
+            .wrap_err_with(|| format!("failed to get seed peers from {url}"))?;
+
+        if response.status() == StatusCode::NOT_MODIFIED && cached.is_some() {
+            return Ok(UrlResponse::NotModified);
+        }
+
+        let response = response
+            .error_for_status()
+            .wrap_err_with(|| format!("failed to get seed peers from {url}"))?;
+
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+
+        let content = response
+            .text()
+            .await
+            .wrap_err_with(|| format!("failed to read seed peers from {url}"))?;
+
+        Ok(UrlResponse::Modified {
+            content,
+            etag,
+            last_modified,
+        })
+    }
+
+    /// Checks content against pinned checksum & detached signature, when configured
+    async fn verify(&self, client: &Client, content: &str) -> eyre::Result<()> {
+        if let Some(expected) = &self.sha256 {
+            let actual = sha256_hex(content.as_bytes());
+            if !actual.eq_ignore_ascii_case(expected.trim()) {
+                bail!(
+                    "checksum mismatch for {}: expected sha256 {expected}, got {actual}",
+                    self.label
+                );
+            }
+        }
+
+        if let Some(signature_url) = &self.signature_url {
+            let signature = client
+                .get(signature_url)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .wrap_err_with(|| format!("failed to get signature from {signature_url}"))?
+                .bytes()
+                .await
+                .wrap_err_with(|| format!("failed to read signature from {signature_url}"))?;
+
+            let mut content_file = NamedTempFile::new()?;
+            content_file.write_all(content.as_bytes())?;
+            let mut signature_file = NamedTempFile::new()?;
+            signature_file.write_all(&signature)?;
+
+            verify_detached_signature(signature_file.path(), content_file.path())
+                .await
+                .wrap_err_with(|| format!("failed to verify seed peers from {}", self.label))?;
+            debug!(source = self.label, "seed source signature verified");
+        }
+
+        Ok(())
+    }
+
+    fn cache_key(&self, url: &str) -> String {
+        match &self.request_body {
+            Some(body) => format!("{url} {body}"),
+            None => url.to_string(),
+        }
+    }
+
+    pub fn parse(&self, content: &str) -> eyre::Result<Vec<HyperliquidSeedPeer>> {
+        let peers: Vec<_> = match self.format {
+            SeedPeerSourceFormat::JsonIpArray => {
+                let ips: Vec<Ipv4Addr> =
+                    serde_json::from_str(content).wrap_err("failed to parse JSON IP array")?;
+                ips.into_iter().map(|ip| (None, ip)).collect()
+            }
+            SeedPeerSourceFormat::OverrideGossipConfig => {
+                let config: OverrideGossipConfig = serde_json::from_str(content)
+                    .wrap_err("failed to parse override_gossip_config")?;
+                config
+                    .root_node_ips
+                    .into_iter()
+                    .map(|node| (None, node.ip))
+                    .collect()
+            }
+            SeedPeerSourceFormat::MarkdownTable => {
+                let section = self
+                    .markdown_section
+                    .as_deref()
+                    .wrap_err("markdown_section is required for markdown_table format")?;
+                parse_markdown_table(content, section)?
+            }
+            SeedPeerSourceFormat::Csv => parse_csv(content),
+            SeedPeerSourceFormat::PlainText => parse_plain_text(content),
+        };
+
+        if peers.is_empty() {
+            bail!("No seed peers were given from {}", self.label);
+        }
+
+        Ok(peers
+            .into_iter()
+            .map(|(operator_name, ip)| HyperliquidSeedPeer {
+                operator_name: operator_name.unwrap_or_else(|| self.label.clone()),
+                sources: vec![self.label.clone()],
+                ip,
+            })
+            .collect())
+    }
+}
+
+fn sha256_hex(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn parse_markdown_table(
+    content: &str,
+    section: &str,
+) -> eyre::Result<Vec<(Option<String>, Ipv4Addr)>> {
+    let mut peers = Vec::new();
+
+    // Find the table section that contains the seed peers
+    let seed_peers_section = content
+        .split(&format!("## {section}"))
+        .nth(1)
+        .wrap_err_with(|| format!("could not find '{section}' section"))?;
+
+    // Split by next section (starts with ##) to isolate just the peers table
+    let peers_content = seed_peers_section
+        .split("##")
+        .next()
+        .unwrap_or(seed_peers_section);
+
+    // Find the table by looking for lines that start and end with |
+    let lines: Vec<&str> = peers_content.lines().collect();
+    let mut in_table = false;
+    let mut header_found = false;
+
+    for line in lines {
+        let trimmed = line.trim();
+
+        // Skip empty lines
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        // Check if this line looks like a markdown table row
+        if trimmed.starts_with('|') && trimmed.ends_with('|') {
+            // Skip separator lines (contains only |, -, and spaces)
+            if trimmed
+                .chars()
+                .all(|c| c == '|' || c == '-' || c.is_whitespace())
+            {
+                in_table = true;
+                continue;
+            }
+
+            // Parse the table cells
+            let cells: Vec<&str> = trimmed
+                .split('|')
+                .map(|cell| cell.trim())
+                .filter(|cell| !cell.is_empty())
+                .collect();
+
+            // Skip header row
+            if !header_found
+                && cells.len() >= 2
+                && (cells[0].to_lowercase().contains("operator")
+                    || cells[1].to_lowercase().contains("root")
+                    || cells[1].to_lowercase().contains("ip"))
+            {
+                header_found = true;
+                in_table = true;
+                continue;
+            }
+
+            // Parse data rows
+            if in_table && header_found && cells.len() >= 2 {
+                let operator_name = cells[0].to_string();
+                let ip_str = cells[1];
+
+                // Parse the IP address
+                match ip_str.parse::<Ipv4Addr>() {
+                    Ok(ip) => peers.push((Some(operator_name), ip)),
+                    Err(err) => {
+                        debug!(?err, ip_str, "failed to parse ip");
+                        continue;
+                    }
+                }
+            }
+        } else if in_table {
+            // If we were in a table but this line doesn't look like a table row,
+            // we've probably reached the end of the table
+            break;
+        }
+    }
+
+    if peers.is_empty() {
+        bail!("No valid seed peers found in markdown table");
+    }
+
+    Ok(peers)
+}
+
+fn parse_csv(content: &str) -> Vec<(Option<String>, Ipv4Addr)> {
+    let mut peers = Vec::new();
+
+    for line in content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+    {
+        let cells: Vec<&str> = line.split(',').map(str::trim).collect();
+        let (operator_name, ip_str) = match cells.as_slice() {
+            [ip_str] => (None, *ip_str),
+            [operator_name, ip_str, ..] => (Some(operator_name.to_string()), *ip_str),
+            [] => continue,
+        };
+
+        // Header rows & garbage fall through here
+        match ip_str.parse::<Ipv4Addr>() {
+            Ok(ip) => peers.push((operator_name, ip)),
+            Err(err) => debug!(?err, ip_str, "failed to parse ip"),
+        }
+    }
+
+    peers
+}
+
+fn parse_plain_text(content: &str) -> Vec<(Option<String>, Ipv4Addr)> {
+    let mut peers = Vec::new();
+
+    for line in content.lines() {
+        let ip_str = line.split('#').next().unwrap_or_default().trim();
+        if ip_str.is_empty() {
+            continue;
+        }
+
+        match ip_str.parse::<Ipv4Addr>() {
+            Ok(ip) => peers.push((None, ip)),
+            Err(err) => debug!(?err, ip_str, "failed to parse ip"),
+        }
+    }
+
+    peers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(format: SeedPeerSourceFormat) -> SeedPeerSource {
+        SeedPeerSource {
+            label: "test".to_string(),
+            location: SeedPeerSourceLocation::Path("/dev/null".into()),
+            format,
+            request_body: None,
+            markdown_section: Some("Seed Peers".to_string()),
+            sha256: None,
+            signature_url: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pinned_checksum() -> eyre::Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let path = temp_dir.path().join("peers.txt");
+        fs::write(&path, "1.2.3.4\n")?;
+
+        let mut source = SeedPeerSource {
+            location: SeedPeerSourceLocation::Path(path.clone()),
+            sha256: Some(sha256_hex(b"1.2.3.4\n").to_uppercase()),
+            ..source(SeedPeerSourceFormat::PlainText)
+        };
+        let client = Client::new();
+        let peer_filter = PeerFilter::default();
+        let mut cache = SeedCache::default();
+        assert_eq!(
+            source.fetch(&client, &peer_filter, &mut cache).await?.len(),
+            1
+        );
+
+        fs::write(&path, "6.6.6.6\n")?;
+        assert!(
+            source
+                .fetch(&client, &peer_filter, &mut cache)
+                .await
+                .is_err()
+        );
+
+        source.sha256 = None;
+        assert_eq!(
+            source.fetch(&client, &peer_filter, &mut cache).await?[0].ip,
+            Ipv4Addr::new(6, 6, 6, 6)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_seed_peer_source_config() -> eyre::Result<()> {
+        let source: SeedPeerSource = serde_json::from_str(
+            r#"{"label": "fleet", "url": "https://fleet.internal/peers", "format": "json_ip_array"}"#,
+        )?;
+        assert!(
+            matches!(source.location, SeedPeerSourceLocation::Url(url) if url == "https://fleet.internal/peers")
+        );
+        assert_eq!(source.format, SeedPeerSourceFormat::JsonIpArray);
+
+        let source: SeedPeerSource = serde_json::from_str(
+            r#"{"label": "local", "path": "/data/peers.txt", "format": "plain_text"}"#,
+        )?;
+        assert!(matches!(source.location, SeedPeerSourceLocation::Path(_)));
+        assert_eq!(source.format, SeedPeerSourceFormat::PlainText);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_markdown_table() -> eyre::Result<()> {
+        let content = r#"
+# Node
+
+## Seed Peers
+
+| Operator | Root IP |
+|----------|---------|
+| Alice    | 1.2.3.4 |
+| Bob      | not-ip  |
+| Carol    | 5.6.7.8 |
+
+## Other section
+
+| Operator | Root IP |
+|----------|---------|
+| Mallory  | 9.9.9.9 |
+"#;
+
+        let peers = source(SeedPeerSourceFormat::MarkdownTable).parse(content)?;
+        let parsed: Vec<_> = peers
+            .iter()
+            .map(|peer| (peer.operator_name.as_str(), peer.ip))
+            .collect();
+        assert_eq!(
+            parsed,
+            vec![
+                ("Alice", Ipv4Addr::new(1, 2, 3, 4)),
+                ("Carol", Ipv4Addr::new(5, 6, 7, 8)),
+            ]
+        );
+
+        let err = source(SeedPeerSourceFormat::MarkdownTable)
+            .parse("## Something else")
+            .unwrap_err();
+        assert!(err.to_string().contains("could not find 'Seed Peers'"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_csv_and_plain_text() -> eyre::Result<()> {
+        let peers = source(SeedPeerSourceFormat::Csv)
+            .parse("operator_name,root_ips\nAlice,1.2.3.4\n5.6.7.8\n")?;
+        assert_eq!(peers.len(), 2);
+        assert_eq!(peers[0].operator_name, "Alice");
+        assert_eq!(peers[1].operator_name, "test");
+        assert_eq!(peers[1].ip, Ipv4Addr::new(5, 6, 7, 8));
+
+        let peers = source(SeedPeerSourceFormat::PlainText)
+            .parse("# fleet\n1.2.3.4 # tokyo\n\n5.6.7.8\n")?;
+        assert_eq!(peers.len(), 2);
+        assert!(peers.iter().all(|peer| peer.sources == ["test"]));
+
+        assert!(source(SeedPeerSourceFormat::PlainText).parse("").is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_conditional_and_cached_fallback() -> eyre::Result<()> {
+        use std::{
+            sync::{
+                Arc,
+                atomic::{AtomicUsize, Ordering},
+            },
+            time::Duration,
+        };
+
+        use axum::{Router, extract::State, http::HeaderMap, routing::get};
+
+        // 1st request: content with ETag, 2nd: not modified, 3rd+: server error
+        async fn peers(
+            State(requests): State<Arc<AtomicUsize>>,
+            headers: HeaderMap,
+        ) -> (StatusCode, [(&'static str, &'static str); 1], &'static str) {
+            match requests.fetch_add(1, Ordering::SeqCst) {
+                0 => (StatusCode::OK, [("etag", "\"v1\"")], "1.2.3.4\n"),
+                1 if headers
+                    .get(IF_NONE_MATCH)
+                    .is_some_and(|etag| etag == "\"v1\"") =>
+                {
+                    (StatusCode::NOT_MODIFIED, [("etag", "\"v1\"")], "")
+                }
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, [("etag", "")], ""),
+            }
+        }
+
+        let requests = Arc::new(AtomicUsize::new(0));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        let router = Router::new()
+            .route("/peers", get(peers))
+            .with_state(requests.clone());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        let source = SeedPeerSource {
+            location: SeedPeerSourceLocation::Url(format!("http://{address}/peers")),
+            ..source(SeedPeerSourceFormat::PlainText)
+        };
+
+        let temp_dir = tempfile::TempDir::new()?;
+        let cache_path = temp_dir.path().join("seed_cache.json");
+        let client = Client::new();
+        let peer_filter = PeerFilter::default();
+
+        let mut cache = SeedCache::load(&cache_path, Duration::from_secs(3600))?;
+        for _ in 0..3 {
+            let peers = source.fetch(&client, &peer_filter, &mut cache).await?;
+            assert_eq!(peers.len(), 1);
+            assert_eq!(peers[0].ip, Ipv4Addr::new(1, 2, 3, 4));
+        }
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+        // Cached content is verified again, so it's not used once the pinned checksum doesn't match it
+        let pinned = SeedPeerSource {
+            sha256: Some(sha256_hex(b"6.6.6.6\n")),
+            ..source.clone()
+        };
+        assert!(
+            pinned
+                .fetch(&client, &peer_filter, &mut cache)
+                .await
+                .is_err()
+        );
+
+        // Nothing to fall back to without cached content
+        let mut cache = SeedCache::default();
+        assert!(
+            source
+                .fetch(&client, &peer_filter, &mut cache)
+                .await
+                .is_err()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+use std::{collections::HashSet, hash::Hash};
+
+use clap::ValueEnum;
+use rand::RngExt;
+
+use crate::speedtest::SeedPeerMeasurement;
+
+/// How seed peers are picked among measured candidates
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum SelectionStrategy {
+    /// Lowest score first
+    #[default]
+    Latency,
+    /// Lowest score first, but at most one peer per operator until every operator is represented
+    OperatorDiverse,
+    /// Lowest score first, but at most one peer per /24 subnet until every subnet is represented
+    SubnetDiverse,
+    /// Random peers, weighted by inverse score
+    WeightedRandom,
+}
+
+/// Selects up to `n` seed peers from measurements ordered by score
+pub fn select_seed_peers(
+    measurements: &[SeedPeerMeasurement],
+    n: usize,
+    strategy: SelectionStrategy,
+) -> Vec<SeedPeerMeasurement> {
+    match strategy {
+        SelectionStrategy::Latency => measurements.iter().take(n).cloned().collect(),
+        SelectionStrategy::OperatorDiverse => select_diverse(measurements, n, |measurement| {
+            measurement.peer.operator_name.trim().to_lowercase()
+        }),
+        SelectionStrategy::SubnetDiverse => select_diverse(measurements, n, |measurement| {
+            let [a, b, c, _] = measurement.peer.ip.octets();
+            [a, b, c]
+        }),
+        SelectionStrategy::WeightedRandom => select_weighted_random(measurements, n),
+    }
+}
+
+fn select_diverse<K: Eq + Hash>(
+    measurements: &[SeedPeerMeasurement],
+    n: usize,
+    key: impl Fn(&SeedPeerMeasurement) -> K,
+) -> Vec<SeedPeerMeasurement> {
+    let mut seen_keys = HashSet::new();
+    let (mut picked, mut rest) = (Vec::new(), Vec::new());
+
+    // Best peer of every group is picked first, the rest fills up remaining slots by score
+    for (idx, measurement) in measurements.iter().enumerate() {
+        if seen_keys.insert(key(measurement)) {
+            picked.push(idx);
+        } else {
+            rest.push(idx);
+        }
+    }
+
+    picked.truncate(n);
+    let missing = n.saturating_sub(picked.len());
+    picked.extend(rest.into_iter().take(missing));
+    picked.sort_unstable();
+
+    picked
+        .into_iter()
+        .map(|idx| measurements[idx].clone())
+        .collect()
+}
+
+fn select_weighted_random(
+    measurements: &[SeedPeerMeasurement],
+    n: usize,
+) -> Vec<SeedPeerMeasurement> {
+    let mut rng = rand::rng();
+    let mut remaining: Vec<_> = measurements.iter().collect();
+    let mut picked = Vec::new();
+
+    while picked.len() < n && !remaining.is_empty() {
+        let weights: Vec<f64> = remaining
+            .iter()
+            .map(|measurement| 1.0 / measurement.score.as_secs_f64().max(1e-6))
+            .collect();
+
+        let mut target = rng.random::<f64>() * weights.iter().sum::<f64>();
+        let idx = weights
+            .iter()
+            .position(|weight| {
+                target -= weight;
+                target <= 0.0
+            })
+            .unwrap_or(remaining.len() - 1);
+
+        picked.push(remaining.remove(idx).clone());
+    }
+
+    // Keep the result ordered by score like other strategies
+    picked.sort_by(|a, b| a.score.cmp(&b.score));
+    picked
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::Ipv4Addr, time::Duration};
+
+    use super::*;
+    use crate::{hl_gossip_config::HyperliquidSeedPeer, speedtest::LatencyStats};
+
+    fn measurement(operator_name: &str, ip: [u8; 4], latency_ms: u64) -> SeedPeerMeasurement {
+        SeedPeerMeasurement {
+            peer: HyperliquidSeedPeer {
+                operator_name: operator_name.to_string(),
+                sources: vec!["test".to_string()],
+                ip: Ipv4Addr::from(ip),
+            },
+            latency: Duration::from_millis(latency_ms),
+            stats: LatencyStats::default(),
+            score: Duration::from_millis(latency_ms),
+        }
+    }
+
+    fn ips(measurements: &[SeedPeerMeasurement]) -> Vec<Ipv4Addr> {
+        measurements
+            .iter()
+            .map(|measurement| measurement.peer.ip)
+            .collect()
+    }
+
+    #[test]
+    fn test_select_diverse() {
+        let measurements = vec![
+            measurement("Alice", [1, 1, 1, 1], 1),
+            measurement("alice", [1, 1, 1, 2], 2),
+            measurement("Alice", [2, 2, 2, 1], 3),
+            measurement("Bob", [1, 1, 1, 3], 4),
+            measurement("Carol", [3, 3, 3, 1], 5),
+        ];
+
+        assert_eq!(
+            ips(&select_seed_peers(
+                &measurements,
+                3,
+                SelectionStrategy::Latency
+            )),
+            vec![
+                Ipv4Addr::new(1, 1, 1, 1),
+                Ipv4Addr::new(1, 1, 1, 2),
+                Ipv4Addr::new(2, 2, 2, 1)
+            ]
+        );
+        assert_eq!(
+            ips(&select_seed_peers(
+                &measurements,
+                3,
+                SelectionStrategy::OperatorDiverse
+            )),
+            vec![
+                Ipv4Addr::new(1, 1, 1, 1),
+                Ipv4Addr::new(1, 1, 1, 3),
+                Ipv4Addr::new(3, 3, 3, 1)
+            ]
+        );
+        assert_eq!(
+            ips(&select_seed_peers(
+                &measurements,
+                4,
+                SelectionStrategy::SubnetDiverse
+            )),
+            vec![
+                Ipv4Addr::new(1, 1, 1, 1),
+                Ipv4Addr::new(1, 1, 1, 2),
+                Ipv4Addr::new(2, 2, 2, 1),
+                Ipv4Addr::new(3, 3, 3, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select_weighted_random() {
+        let measurements = vec![
+            measurement("Alice", [1, 1, 1, 1], 1),
+            measurement("Bob", [2, 2, 2, 2], 2),
+            measurement("Carol", [3, 3, 3, 3], 3),
+        ];
+
+        let picked = select_seed_peers(&measurements, 2, SelectionStrategy::WeightedRandom);
+        assert_eq!(picked.len(), 2);
+        assert_ne!(picked[0].peer.ip, picked[1].peer.ip);
+        assert!(picked[0].score <= picked[1].score);
+
+        let picked = select_seed_peers(&measurements, 5, SelectionStrategy::WeightedRandom);
+        assert_eq!(picked.len(), 3);
+    }
+}
@@ -1,62 +1,407 @@
 use std::{
     fmt,
+    io::ErrorKind,
     net::{Ipv4Addr, SocketAddr},
+    str::FromStr,
     sync::Arc,
     time::Duration,
 };
 
+use clap::ValueEnum;
+use eyre::bail;
 use tokio::{
     net::TcpStream,
     sync::Semaphore,
+    task::JoinSet,
     time::{Instant, timeout},
 };
 use tracing::{Level, debug, info, trace};
 
-use crate::hl_gossip_config::HyperliquidSeedPeer;
+use crate::{
+    hl_gossip_config::HyperliquidSeedPeer,
+    peer_db::PeerDb,
+    selection::{SelectionStrategy, select_seed_peers},
+};
+
+/// Shortest per-connection timeout. Peers slower than the latency threshold are still reachable, so they're given
+/// more time to connect than the threshold, to tell them apart from the ones which are down
+const MIN_CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Which latency statistic seed peers are ranked & filtered by
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum LatencyStatistic {
+    Min,
+    #[default]
+    Median,
+    P90,
+    /// Kernel's smoothed RTT from TCP_INFO, falls back to median where unavailable
+    KernelRtt,
+}
+
+/// Deeper check that the remote end behaves like a gossip endpoint, rather than just accepting TCP connections
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum GossipProbe {
+    #[default]
+    Off,
+    /// Connection has to stay open for the probe timeout, catching firewalls & load balancers which accept-then-drop
+    Hold,
+    /// Remote end has to send something within the probe timeout
+    Banner,
+}
+
+/// Gossip port or inclusive port range, e.g. `4001` or `4000-4010`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PortRange {
+    start: u16,
+    end: u16,
+}
+
+impl PortRange {
+    fn ports(&self) -> impl Iterator<Item = u16> + use<> {
+        self.start..=self.end
+    }
+}
+
+impl FromStr for PortRange {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = match s.split_once('-') {
+            Some((start, end)) => (start.trim().parse()?, end.trim().parse()?),
+            None => {
+                let port = s.trim().parse()?;
+                (port, port)
+            }
+        };
+        if start > end {
+            bail!("port range '{s}' is empty");
+        }
+
+        Ok(Self { start, end })
+    }
+}
+
+impl fmt::Display for PortRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}", self.start)
+        } else {
+            write!(f, "{}-{}", self.start, self.end)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SpeedtestOptions {
+    /// Connections made per peer
+    pub samples: usize,
+    pub ports: PortRange,
+    pub rank_by: LatencyStatistic,
+    /// Reachable peers above this are not picked, but aren't counted as failing either
+    pub max_latency: Duration,
+    pub probe: GossipProbe,
+    pub probe_timeout: Duration,
+}
+
+impl SpeedtestOptions {
+    fn connect_timeout(&self) -> Duration {
+        self.max_latency.max(MIN_CONNECT_TIMEOUT)
+    }
+}
 
 #[derive(Debug)]
-enum MeasureError {
+pub enum MeasureError {
     Timeout,
     IOError(std::io::Error),
+    ProbeFailed(&'static str),
 }
 
 impl fmt::Display for MeasureError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Timeout => f.debug_tuple("Timeout").finish(),
-            Self::IOError(err) => f.debug_tuple("IOError").field(&err).finish(),
+            Self::Timeout => write!(f, "timed out"),
+            Self::IOError(err) => write!(f, "{err}"),
+            Self::ProbeFailed(reason) => write!(f, "gossip probe failed: {reason}"),
         }
     }
 }
 
-// TODO: return failure reason for debugging
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LatencyStats {
+    pub min: Duration,
+    pub median: Duration,
+    pub p90: Duration,
+    /// Mean difference between consecutive samples
+    pub jitter: Duration,
+    /// Median of kernel smoothed RTT readings, if available
+    pub kernel_rtt: Option<Duration>,
+    /// Port which accepted connections
+    pub port: u16,
+    pub samples: usize,
+    pub lost: usize,
+}
+
+impl LatencyStats {
+    /// `samples` are connect times in measurement order
+    fn from_samples(
+        samples: &[Duration],
+        kernel_rtts: &[Duration],
+        port: u16,
+        lost: usize,
+    ) -> Option<Self> {
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let (&min, _) = sorted.split_first()?;
+
+        let jitter = match samples.len() {
+            0 | 1 => Duration::ZERO,
+            len => {
+                samples
+                    .windows(2)
+                    .map(|pair| pair[0].abs_diff(pair[1]))
+                    .sum::<Duration>()
+                    / (len - 1) as u32
+            }
+        };
+
+        let mut kernel_rtts = kernel_rtts.to_vec();
+        kernel_rtts.sort();
+
+        Some(Self {
+            min,
+            median: median(&sorted)?,
+            p90: sorted[(sorted.len() * 9).div_ceil(10) - 1],
+            jitter,
+            kernel_rtt: median(&kernel_rtts),
+            port,
+            samples: samples.len(),
+            lost,
+        })
+    }
+
+    pub fn get(&self, statistic: LatencyStatistic) -> Duration {
+        match statistic {
+            LatencyStatistic::Min => self.min,
+            LatencyStatistic::Median => self.median,
+            LatencyStatistic::P90 => self.p90,
+            LatencyStatistic::KernelRtt => self.kernel_rtt.unwrap_or(self.median),
+        }
+    }
+}
+
+fn median(sorted: &[Duration]) -> Option<Duration> {
+    if sorted.is_empty() {
+        return None;
+    }
+
+    let mid = sorted.len() / 2;
+    Some(if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2
+    } else {
+        sorted[mid]
+    })
+}
+
+struct Sample {
+    port: u16,
+    connect: Duration,
+    kernel_rtt: Option<Duration>,
+}
+
+/// Takes `options.samples` connection samples. Peer counts as reachable as long as any of them succeeds, so a single
+/// SYN retransmit or cold ARP cache does not disqualify it.
 async fn measure_node_latency(
+    ip: Ipv4Addr,
+    options: SpeedtestOptions,
+) -> Result<LatencyStats, MeasureError> {
+    let mut port = None;
+    let mut samples = Vec::new();
+    let mut kernel_rtts = Vec::new();
+    let mut last_err = MeasureError::Timeout;
+
+    for _ in 0..options.samples.max(1) {
+        let sample = match port {
+            Some(port) => connect_sample(ip, port, options.connect_timeout()).await,
+            None => find_port(ip, options.ports, options.connect_timeout()).await,
+        };
+
+        match sample {
+            Ok(sample) => {
+                port = Some(sample.port);
+                samples.push(sample.connect);
+                kernel_rtts.extend(sample.kernel_rtt);
+            }
+            Err(err) => last_err = err,
+        }
+    }
+
+    let lost = options.samples.max(1) - samples.len();
+    LatencyStats::from_samples(&samples, &kernel_rtts, port.unwrap_or_default(), lost)
+        .ok_or(last_err)
+}
+
+/// Connects to all ports in the range at once, returning the first successful connection
+async fn find_port(
+    ip: Ipv4Addr,
+    ports: PortRange,
+    timeout_duration: Duration,
+) -> Result<Sample, MeasureError> {
+    let mut attempts = JoinSet::new();
+    for port in ports.ports() {
+        attempts.spawn(connect_sample(ip, port, timeout_duration));
+    }
+
+    let mut last_err = MeasureError::Timeout;
+    while let Some(result) = attempts.join_next().await {
+        match result {
+            Ok(Ok(sample)) => return Ok(sample),
+            Ok(Err(err)) => last_err = err,
+            Err(err) => last_err = MeasureError::IOError(std::io::Error::other(err)),
+        }
+    }
+
+    Err(last_err)
+}
+
+async fn connect_sample(
     ip: Ipv4Addr,
     port: u16,
     timeout_duration: Duration,
-) -> Result<Duration, MeasureError> {
+) -> Result<Sample, MeasureError> {
     let addr = SocketAddr::new(ip.into(), port);
     let start = Instant::now();
 
     match timeout(timeout_duration, TcpStream::connect(addr)).await {
-        Ok(Ok(_)) => Ok(start.elapsed()),
+        Ok(Ok(stream)) => Ok(Sample {
+            port,
+            connect: start.elapsed(),
+            kernel_rtt: kernel_rtt(&stream),
+        }),
         Ok(Err(err)) => Err(MeasureError::IOError(err)),
         Err(/* Elapsed */ _) => Err(MeasureError::Timeout),
     }
 }
 
+/// Opens a fresh connection & checks how the remote end behaves within the probe timeout
+async fn probe_gossip(
+    ip: Ipv4Addr,
+    port: u16,
+    probe: GossipProbe,
+    probe_timeout: Duration,
+) -> Result<(), MeasureError> {
+    if probe == GossipProbe::Off {
+        return Ok(());
+    }
+
+    let addr = SocketAddr::new(ip.into(), port);
+    let stream = match timeout(probe_timeout, TcpStream::connect(addr)).await {
+        Ok(Ok(stream)) => stream,
+        Ok(Err(err)) => return Err(MeasureError::IOError(err)),
+        Err(_) => return Err(MeasureError::Timeout),
+    };
+
+    let read = async {
+        let mut buf = [0_u8; 64];
+        loop {
+            stream.readable().await?;
+            match stream.try_read(&mut buf) {
+                Ok(n) => return Ok(n),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => continue,
+                Err(err) => return Err(err),
+            }
+        }
+    };
+
+    match timeout(probe_timeout, read).await {
+        Ok(Ok(0)) => Err(MeasureError::ProbeFailed("connection closed by peer")),
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(err)) => Err(MeasureError::IOError(err)),
+        Err(_) if probe == GossipProbe::Hold => Ok(()),
+        Err(_) => Err(MeasureError::ProbeFailed("nothing received")),
+    }
+}
+
+/// Kernel's smoothed RTT estimate of the connection. Read right after connecting, it's based on the handshake like the
+/// connect time, but is timestamped by the kernel rather than affected by runtime scheduling delays.
+#[cfg(target_os = "linux")]
+fn kernel_rtt(stream: &TcpStream) -> Option<Duration> {
+    use std::os::fd::AsRawFd;
+
+    // SAFETY: tcp_info is plain old data, for which all zeroes is a valid value
+    let mut info: libc::tcp_info = unsafe { std::mem::zeroed() };
+    let mut len = size_of::<libc::tcp_info>() as libc::socklen_t;
+    // SAFETY: the fd is a valid socket borrowed from `stream` for the duration of the call, and getsockopt writes at
+    // most `len` bytes into `info`, which is exactly its size
+    let ret = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::IPPROTO_TCP,
+            libc::TCP_INFO,
+            (&mut info as *mut libc::tcp_info).cast(),
+            &mut len,
+        )
+    };
+
+    (ret == 0 && info.tcpi_rtt > 0).then(|| Duration::from_micros(info.tcpi_rtt as u64))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn kernel_rtt(_stream: &TcpStream) -> Option<Duration> {
+    None
+}
+
+#[derive(Clone, Debug)]
+pub struct SeedPeerMeasurement {
+    pub peer: HyperliquidSeedPeer,
+    /// Latency statistic peers are ranked by
+    pub latency: Duration,
+    pub stats: LatencyStats,
+    /// Effective latency used for ranking, see [`PeerDb::score`]
+    pub score: Duration,
+}
+
+/// Measures latency to the candidates & picks `n` of them using given strategy
 pub async fn speedtest_nodes(
     candidates: Vec<HyperliquidSeedPeer>,
     n: usize,
-    timeout_duration: Duration,
-) -> eyre::Result<Vec<HyperliquidSeedPeer>> {
-    // NOTE: Gossip port is 4001 as of 2025-07-23, could change in the future
-    let port = 4001;
+    strategy: SelectionStrategy,
+    options: SpeedtestOptions,
+    peer_db: &mut PeerDb,
+) -> eyre::Result<Vec<SeedPeerMeasurement>> {
+    let measurements = measure_seed_peers(candidates, options, peer_db).await?;
+
+    Ok(pick_seed_peers(&measurements, n, strategy, peer_db))
+}
+
+/// Measures latency to the candidates, returning ones within the latency threshold ordered by score. Only peers
+/// which can't be connected to or fail the gossip probe are recorded as failing
+pub async fn measure_seed_peers(
+    candidates: Vec<HyperliquidSeedPeer>,
+    options: SpeedtestOptions,
+    peer_db: &mut PeerDb,
+) -> eyre::Result<Vec<SeedPeerMeasurement>> {
     let concurrency = 64;
 
+    // Skip peers which have been failing repeatedly, unless there's nothing else left
+    let (banned, candidates): (Vec<_>, Vec<_>) = candidates
+        .into_iter()
+        .partition(|node| peer_db.is_banned(&node.ip));
+    let candidates = if candidates.is_empty() {
+        banned
+    } else {
+        if !banned.is_empty() {
+            debug!(?banned, "skipping temporarily banned seed nodes");
+        }
+        candidates
+    };
+
     info!(
         candidates = candidates.len(),
-        concurrency, "testing latency to seed nodes"
+        concurrency,
+        samples = options.samples,
+        ports = %options.ports,
+        probe = ?options.probe,
+        "testing latency to seed nodes"
     );
 
     // Use semaphore to limit concurrent connections
@@ -69,27 +414,45 @@ pub async fn speedtest_nodes(
 
         let task = tokio::spawn(async move {
             let _permit = sem.acquire().await.unwrap();
-            let latency = measure_node_latency(ip, port, timeout_duration).await;
-            (idx, latency)
+            let stats = match measure_node_latency(ip, options).await {
+                Ok(stats) => probe_gossip(ip, stats.port, options.probe, options.probe_timeout)
+                    .await
+                    .map(|_| stats),
+                Err(err) => Err(err),
+            };
+            (idx, stats)
         });
 
         tasks.push(task);
     }
 
     let mut successful_nodes = Vec::new();
-    let mut failed = 0;
+    let (mut too_slow, mut failed) = (0, 0);
 
     for task in tasks {
-        let (idx, latency) = task.await?;
+        let (idx, stats) = task.await?;
         let node = &candidates[idx];
 
-        match latency {
-            Ok(latency) => {
-                trace!(?node, ?latency, "latency test ok");
-                successful_nodes.push((idx, latency));
+        match stats {
+            Ok(stats) => {
+                trace!(?node, ?stats, "latency test ok");
+                let latency = stats.get(options.rank_by);
+                peer_db.record_success(node, latency);
+                if latency > options.max_latency {
+                    trace!(?node, ?latency, "seed node above latency threshold");
+                    too_slow += 1;
+                    continue;
+                }
+                successful_nodes.push(SeedPeerMeasurement {
+                    peer: node.clone(),
+                    latency,
+                    stats,
+                    score: peer_db.score(&node.ip, latency),
+                });
             }
             Err(err) => {
-                trace!(%err, ?node, "latency test failed");
+                debug!(%err, ?node, "latency test failed");
+                peer_db.record_failure(node, &err.to_string());
                 failed += 1;
             }
         }
@@ -97,33 +460,191 @@ pub async fn speedtest_nodes(
 
     info!(
         successful = successful_nodes.len(),
-        failed = failed,
-        "latency test complete"
+        too_slow, failed, "latency test complete"
     );
 
-    // Sort by latency (lowest first)
-    successful_nodes.sort_by(|a, b| a.1.cmp(&b.1));
+    // Sort by score (lowest first), which takes peer history into account
+    successful_nodes.sort_by(|a, b| a.score.cmp(&b.score));
 
-    // NOTE: this could be more efficient, but I want to log all the nodes
+    if tracing::enabled!(Level::DEBUG) {
+        for (idx, measurement) in successful_nodes.iter().enumerate() {
+            debug!(
+                idx,
+                node = ?measurement.peer,
+                latency = ?measurement.latency,
+                min = ?measurement.stats.min,
+                median = ?measurement.stats.median,
+                p90 = ?measurement.stats.p90,
+                jitter = ?measurement.stats.jitter,
+                kernel_rtt = ?measurement.stats.kernel_rtt,
+                lost = measurement.stats.lost,
+                score = ?measurement.score,
+                "seed node measurement"
+            );
+        }
+    }
 
-    // Return the n lowest latency nodes
-    let to_take = n.min(successful_nodes.len());
-    let result: Vec<_> = successful_nodes
-        .into_iter()
-        .map(|(idx, latency)| (candidates[idx].clone(), latency)) // TODO: too lazy to remove this clone
-        .collect();
+    Ok(successful_nodes)
+}
 
-    if tracing::enabled!(Level::DEBUG) {
-        for (idx, (node, latency)) in result.iter().enumerate() {
-            debug!(idx, ?node, ?latency, "seed node measurement");
+/// Picks up to `n` measured seed peers using given strategy
+pub fn pick_seed_peers(
+    measurements: &[SeedPeerMeasurement],
+    n: usize,
+    strategy: SelectionStrategy,
+    peer_db: &mut PeerDb,
+) -> Vec<SeedPeerMeasurement> {
+    let picked = select_seed_peers(measurements, n, strategy);
+    record_picked_seed_peers(&picked, peer_db);
+
+    picked
+}
+
+pub fn record_picked_seed_peers(picked: &[SeedPeerMeasurement], peer_db: &mut PeerDb) {
+    for (idx, measurement) in picked.iter().enumerate() {
+        info!(
+            idx,
+            node = ?measurement.peer,
+            latency = ?measurement.latency,
+            score = ?measurement.score,
+            "picked seed node"
+        );
+        peer_db.record_selected(&measurement.peer.ip);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_stats() {
+        let samples: Vec<_> = [12, 10, 30, 11].map(Duration::from_millis).into();
+        let stats = LatencyStats::from_samples(&samples, &[], 4001, 1).unwrap();
+
+        assert_eq!(stats.min, Duration::from_millis(10));
+        assert_eq!(stats.median, Duration::from_micros(11_500));
+        assert_eq!(stats.p90, Duration::from_millis(30));
+        assert_eq!(stats.jitter, Duration::from_millis(41) / 3);
+        assert_eq!(
+            stats.get(LatencyStatistic::KernelRtt),
+            Duration::from_micros(11_500)
+        );
+
+        assert!(LatencyStats::from_samples(&[], &[], 4001, 3).is_none());
+    }
+
+    #[test]
+    fn test_parse_port_range() -> eyre::Result<()> {
+        assert_eq!(
+            "4001".parse::<PortRange>()?.ports().collect::<Vec<_>>(),
+            [4001]
+        );
+        assert_eq!("4000-4010".parse::<PortRange>()?.ports().count(), 11);
+        assert!("4010-4000".parse::<PortRange>().is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_measure_node_latency() -> eyre::Result<()> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let port = listener.local_addr()?.port();
+        tokio::spawn(async move { while listener.accept().await.is_ok() {} });
+
+        let options = SpeedtestOptions {
+            samples: 3,
+            ports: PortRange {
+                start: port - 1,
+                end: port,
+            },
+            rank_by: LatencyStatistic::Median,
+            max_latency: Duration::from_secs(1),
+            probe: GossipProbe::Off,
+            probe_timeout: Duration::from_secs(1),
+        };
+        let stats = measure_node_latency(Ipv4Addr::LOCALHOST, options)
+            .await
+            .map_err(|err| eyre::eyre!("{err}"))?;
+        assert_eq!(stats.port, port);
+        assert_eq!(stats.samples, 3);
+        assert_eq!(stats.lost, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_measure_seed_peers_above_threshold() -> eyre::Result<()> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let port = listener.local_addr()?.port();
+        tokio::spawn(async move { while listener.accept().await.is_ok() {} });
+
+        let peer = HyperliquidSeedPeer {
+            operator_name: "test".to_string(),
+            sources: vec!["test".to_string()],
+            ip: Ipv4Addr::LOCALHOST,
+        };
+        let options = SpeedtestOptions {
+            samples: 1,
+            ports: PortRange {
+                start: port,
+                end: port,
+            },
+            rank_by: LatencyStatistic::Median,
+            max_latency: Duration::ZERO,
+            probe: GossipProbe::Off,
+            probe_timeout: Duration::from_secs(1),
+        };
+
+        // Reachable, but slower than the threshold: not picked, yet not counted as failing
+        let mut peer_db = PeerDb::default();
+        for _ in 0..5 {
+            let measurements =
+                measure_seed_peers(vec![peer.clone()], options, &mut peer_db).await?;
+            assert!(measurements.is_empty());
         }
+        let record = peer_db.get(&peer.ip).unwrap();
+        assert_eq!((record.successes, record.failures), (5, 0));
+        assert!(!peer_db.is_banned(&peer.ip));
+
+        Ok(())
     }
 
-    Ok(result
-        .into_iter()
-        .take(to_take)
-        .enumerate()
-        .inspect(|(idx, (node, latency))| info!(idx, ?node, ?latency, "picked seed node"))
-        .map(|(_, (node, _))| node)
-        .collect())
+    #[tokio::test]
+    async fn test_probe_gossip() -> eyre::Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        async fn serve(behavior: u8) -> eyre::Result<u16> {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+            let port = listener.local_addr()?.port();
+            tokio::spawn(async move {
+                let mut held = Vec::new();
+                while let Ok((mut stream, _)) = listener.accept().await {
+                    match behavior {
+                        // Accept-then-drop
+                        0 => drop(stream),
+                        1 => held.push(stream),
+                        _ => {
+                            let _ = stream.write_all(b"hello").await;
+                            held.push(stream);
+                        }
+                    }
+                }
+            });
+
+            Ok(port)
+        }
+
+        let timeout = Duration::from_millis(200);
+        let (dropping, holding, talking) = (serve(0).await?, serve(1).await?, serve(2).await?);
+        let probe = |port, probe| probe_gossip(Ipv4Addr::LOCALHOST, port, probe, timeout);
+
+        assert!(probe(dropping, GossipProbe::Off).await.is_ok());
+        assert!(probe(dropping, GossipProbe::Hold).await.is_err());
+        assert!(probe(holding, GossipProbe::Hold).await.is_ok());
+        assert!(probe(holding, GossipProbe::Banner).await.is_err());
+        assert!(probe(talking, GossipProbe::Banner).await.is_ok());
+
+        Ok(())
+    }
 }
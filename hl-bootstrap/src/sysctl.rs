@@ -1,6 +1,14 @@
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+};
 
+use clap::ValueEnum;
 use eyre::Context;
+use nix::unistd::geteuid;
+use serde::Serialize;
+use tracing::{info, warn};
 
 pub fn read_sysctl(key: &str) -> eyre::Result<String> {
     let key_normalized = key.replace('/', ".");
@@ -10,3 +18,289 @@ pub fn read_sysctl(key: &str) -> eyre::Result<String> {
 
     Ok(value.trim().to_string())
 }
+
+/// Whether IPv6 is enabled for all interfaces, None when it can't be determined
+pub fn ipv6_enabled() -> Option<bool> {
+    read_sysctl("net.ipv6.conf.all.disable_ipv6")
+        .ok()
+        .map(|value| value == "0")
+}
+
+pub fn write_sysctl(key: &str, value: &str) -> eyre::Result<()> {
+    let key_normalized = key.replace('/', ".");
+    let path = PathBuf::from("/proc/sys").join(key.replace('.', "/"));
+    fs::write(path, value).wrap_err_with(|| format!("failed to write sysctl {key_normalized}"))
+}
+
+/// Inodes of initial namespaces, see include/linux/proc_ns.h. Initial network namespace only has a fixed one on
+/// recent kernels, older ones allocate it dynamically from 0xF0000000 up.
+const PROC_PID_INIT_INO: u64 = 0xEFFFFFFC;
+const PROC_NET_INIT_INO: u64 = 0xEFFFFFF9;
+
+/// Where a sysctl takes effect
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum SysctlScope {
+    /// Per network namespace, so a container with its own network can set it for itself (e.g. docker --sysctl)
+    NetworkNamespace,
+    /// Kernel-wide, has to be set on the host
+    Host,
+}
+
+impl SysctlScope {
+    /// Per network namespace sysctls affect the host as well when the namespace is shared with it, which is assumed
+    /// when it can't be determined
+    fn effective(self, shares_host_network_namespace: Option<bool>) -> Self {
+        match (self, shares_host_network_namespace) {
+            (Self::NetworkNamespace, Some(true) | None) => Self::Host,
+            (scope, _) => scope,
+        }
+    }
+}
+
+/// Whether hl-bootstrap runs in host's network namespace, e.g. outside of containers or with docker --network host.
+/// None when it can't be determined, i.e. inside a PID namespace on kernels without fixed initial namespace inodes.
+pub fn shares_host_network_namespace() -> Option<bool> {
+    let inode = |path: &str| fs::metadata(path).ok().map(|metadata| metadata.ino());
+    let net = inode("/proc/self/ns/net")?;
+    if net == PROC_NET_INIT_INO {
+        return Some(true);
+    }
+
+    // PID 1 is host's init only in the initial PID namespace
+    if inode("/proc/self/ns/pid")? != PROC_PID_INIT_INO {
+        return None;
+    }
+    Some(net == inode("/proc/1/ns/net")?)
+}
+
+/// Tunable with acceptable range of its `field`th whitespace separated value
+pub struct RecommendedSysctl {
+    pub key: &'static str,
+    pub field: usize,
+    pub min: u64,
+    pub max: Option<u64>,
+    /// Value written when applying
+    pub value: &'static str,
+    pub scope: SysctlScope,
+}
+
+/// Tunables for gossip nodes handling many connections & high throughput
+pub const RECOMMENDED_SYSCTLS: &[RecommendedSysctl] = &[
+    RecommendedSysctl {
+        key: "net.core.rmem_max",
+        field: 0,
+        min: 16_777_216,
+        max: None,
+        value: "16777216",
+        scope: SysctlScope::Host,
+    },
+    RecommendedSysctl {
+        key: "net.core.wmem_max",
+        field: 0,
+        min: 16_777_216,
+        max: None,
+        value: "16777216",
+        scope: SysctlScope::Host,
+    },
+    RecommendedSysctl {
+        key: "net.core.netdev_max_backlog",
+        field: 0,
+        min: 16_384,
+        max: None,
+        value: "16384",
+        scope: SysctlScope::Host,
+    },
+    RecommendedSysctl {
+        key: "net.netfilter.nf_conntrack_max",
+        field: 0,
+        min: 262_144,
+        max: None,
+        value: "262144",
+        scope: SysctlScope::Host,
+    },
+    RecommendedSysctl {
+        key: "net.core.somaxconn",
+        field: 0,
+        min: 4_096,
+        max: None,
+        value: "4096",
+        scope: SysctlScope::NetworkNamespace,
+    },
+    RecommendedSysctl {
+        key: "net.ipv4.tcp_rmem",
+        field: 2,
+        min: 16_777_216,
+        max: None,
+        value: "4096 87380 16777216",
+        scope: SysctlScope::NetworkNamespace,
+    },
+    RecommendedSysctl {
+        key: "net.ipv4.tcp_wmem",
+        field: 2,
+        min: 16_777_216,
+        max: None,
+        value: "4096 65536 16777216",
+        scope: SysctlScope::NetworkNamespace,
+    },
+    RecommendedSysctl {
+        key: "net.ipv4.tcp_tw_reuse",
+        field: 0,
+        min: 1,
+        max: Some(1),
+        value: "1",
+        scope: SysctlScope::NetworkNamespace,
+    },
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SysctlState {
+    Ok,
+    Deviates,
+    /// Key does not exist, e.g. conntrack module not loaded
+    Unavailable,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SysctlReport {
+    pub key: &'static str,
+    pub current: Option<String>,
+    pub recommended: &'static str,
+    /// Where writing it takes effect, host for per namespace sysctls when the namespace is shared with the host
+    pub scope: SysctlScope,
+    pub state: SysctlState,
+}
+
+impl RecommendedSysctl {
+    fn is_satisfied_by(&self, current: &str) -> bool {
+        current
+            .split_whitespace()
+            .nth(self.field)
+            .and_then(|value| value.parse::<u64>().ok())
+            .is_some_and(|value| value >= self.min && self.max.is_none_or(|max| value <= max))
+    }
+
+    fn report(&self, shares_host_network_namespace: Option<bool>) -> SysctlReport {
+        // Multi-value sysctls are tab separated
+        let current = read_sysctl(self.key)
+            .ok()
+            .map(|value| value.split_whitespace().collect::<Vec<_>>().join(" "));
+        let state = match &current {
+            Some(current) if self.is_satisfied_by(current) => SysctlState::Ok,
+            Some(_) => SysctlState::Deviates,
+            None => SysctlState::Unavailable,
+        };
+
+        SysctlReport {
+            key: self.key,
+            current,
+            recommended: self.value,
+            scope: self.scope.effective(shares_host_network_namespace),
+            state,
+        }
+    }
+}
+
+pub fn check_sysctls() -> Vec<SysctlReport> {
+    let shared = shares_host_network_namespace();
+    RECOMMENDED_SYSCTLS
+        .iter()
+        .map(|spec| spec.report(shared))
+        .collect()
+}
+
+/// Whether hl-bootstrap runs in a container, where host-wide sysctls must not be touched
+pub fn in_container() -> bool {
+    Path::new("/.dockerenv").exists()
+        || Path::new("/run/.containerenv").exists()
+        || std::env::var_os("container").is_some()
+}
+
+/// Writes recommended values of deviating sysctls in given scopes. Per network namespace sysctls count as host-wide
+/// ones when the namespace is shared with the host, so the host is only changed when explicitly asked for.
+pub fn apply_sysctls(scopes: &[SysctlScope]) {
+    if !geteuid().is_root() {
+        warn!("not running as root, unable to apply sysctls");
+        return;
+    }
+    let shared = shares_host_network_namespace();
+    if shared.is_none() && !scopes.contains(&SysctlScope::Host) {
+        warn!(
+            "unable to determine whether network namespace is shared with the host, treating per namespace sysctls \
+             as host-wide"
+        );
+    }
+
+    for spec in RECOMMENDED_SYSCTLS {
+        let report = spec.report(shared);
+        if report.state != SysctlState::Deviates {
+            continue;
+        }
+        if !scopes.contains(&report.scope) {
+            warn!(
+                key = spec.key,
+                current = report.current,
+                recommended = spec.value,
+                scope = ?report.scope,
+                "sysctl deviates from recommended value, but applying it in its scope is not enabled"
+            );
+            continue;
+        }
+
+        match write_sysctl(spec.key, spec.value) {
+            Ok(()) => info!(
+                key = spec.key,
+                previous = report.current,
+                value = spec.value,
+                "applied sysctl"
+            ),
+            // /proc/sys is mounted read-only in unprivileged containers
+            Err(err) => warn!(
+                ?err,
+                key = spec.key,
+                "failed to apply sysctl, set it via container runtime (e.g. docker run --sysctl) instead"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recommended_sysctl_range() {
+        let tcp_rmem = RECOMMENDED_SYSCTLS
+            .iter()
+            .find(|spec| spec.key == "net.ipv4.tcp_rmem")
+            .unwrap();
+        assert!(tcp_rmem.is_satisfied_by("4096\t131072\t33554432"));
+        assert!(!tcp_rmem.is_satisfied_by("4096 131072 6291456"));
+        assert!(!tcp_rmem.is_satisfied_by("4096"));
+
+        let tcp_tw_reuse = RECOMMENDED_SYSCTLS
+            .iter()
+            .find(|spec| spec.key == "net.ipv4.tcp_tw_reuse")
+            .unwrap();
+        assert!(tcp_tw_reuse.is_satisfied_by("1"));
+        assert!(!tcp_tw_reuse.is_satisfied_by("2"));
+    }
+
+    #[test]
+    fn test_effective_scope() {
+        let namespace = SysctlScope::NetworkNamespace;
+        assert_eq!(namespace.effective(Some(false)), namespace);
+        assert_eq!(namespace.effective(None), SysctlScope::Host);
+        assert_eq!(namespace.effective(Some(true)), SysctlScope::Host);
+        assert_eq!(SysctlScope::Host.effective(Some(false)), SysctlScope::Host);
+
+        // Without knowing whether network namespace is shared, per namespace sysctls need host scope to be applied
+        let somaxconn = RECOMMENDED_SYSCTLS
+            .iter()
+            .find(|spec| spec.key == "net.core.somaxconn")
+            .unwrap();
+        assert_eq!(somaxconn.report(None).scope, SysctlScope::Host);
+        assert_eq!(somaxconn.report(Some(false)).scope, namespace);
+    }
+}
@@ -0,0 +1,210 @@
+use std::{
+    convert::Infallible,
+    fmt,
+    fs::{self, File, Permissions},
+    io::ErrorKind,
+    os::unix::fs::{MetadataExt, PermissionsExt},
+    path::Path,
+    str::FromStr,
+};
+
+use eyre::{Context, ContextCompat, bail};
+use nix::unistd::geteuid;
+use serde_json::{Map, Value};
+use tempfile::NamedTempFile;
+use tracing::{debug, info, warn};
+
+/// Validator signer private key, never printed
+#[derive(Clone)]
+pub struct SignerKey(String);
+
+impl fmt::Debug for SignerKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SignerKey(<redacted>)")
+    }
+}
+
+impl FromStr for SignerKey {
+    type Err = eyre::Report;
+
+    /// Expects `0x` followed by 64 hex digits
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let Some(hex) = s.strip_prefix("0x") else {
+            bail!("signer key has to start with 0x");
+        };
+        if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            bail!("signer key has to be 32 bytes in hex");
+        }
+
+        Ok(Self(s.to_string()))
+    }
+}
+
+/// Signer key as given in args, parsed only when needed so that an invalid key does not end up in error messages
+#[derive(Clone)]
+pub struct RawSignerKey(String);
+
+impl fmt::Debug for RawSignerKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RawSignerKey(<redacted>)")
+    }
+}
+
+impl FromStr for RawSignerKey {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl RawSignerKey {
+    pub fn parse(&self) -> eyre::Result<SignerKey> {
+        self.0.parse()
+    }
+}
+
+impl SignerKey {
+    /// Reads the key from a file, refusing files readable by anyone else but the owner
+    pub fn load<P: AsRef<Path>>(path: P) -> eyre::Result<Self> {
+        let path = path.as_ref();
+        check_key_file_permissions(path)?;
+
+        fs::read_to_string(path)
+            .wrap_err_with(|| format!("failed to read signer key from {path:?}"))?
+            .parse()
+            .wrap_err_with(|| format!("invalid signer key in {path:?}"))
+    }
+}
+
+/// Key files must be owned by the current user & not accessible by group or others
+pub fn check_key_file_permissions(path: &Path) -> eyre::Result<()> {
+    let metadata =
+        fs::metadata(path).wrap_err_with(|| format!("failed to read metadata of {path:?}"))?;
+
+    let mode = metadata.mode() & 0o777;
+    if mode & 0o077 != 0 {
+        bail!(
+            "{path:?} has unsafe permissions {mode:o}, it must not be accessible by group or others (chmod 600)"
+        );
+    }
+
+    let uid = geteuid().as_raw();
+    if metadata.uid() != uid {
+        bail!(
+            "{path:?} is owned by uid {}, expected it to be owned by current user (uid {uid})",
+            metadata.uid()
+        );
+    }
+
+    Ok(())
+}
+
+/// Sets the signer key in node_config.json, keeping other keys & making sure the file is only readable by the owner.
+/// Without a key, existing node_config.json has to already contain a valid one.
+pub fn prepare_node_config(path: &Path, key: Option<&SignerKey>) -> eyre::Result<()> {
+    let mut config = match File::open(path) {
+        Ok(file) => serde_json::from_reader::<_, Map<String, Value>>(file)
+            .wrap_err_with(|| format!("failed to parse node config at {path:?}"))?,
+        Err(err) if err.kind() == ErrorKind::NotFound => Map::new(),
+        Err(err) => {
+            return Err(err).wrap_err_with(|| format!("failed to open node config at {path:?}"));
+        }
+    };
+
+    let Some(key) = key else {
+        let existing = config
+            .get("key")
+            .and_then(Value::as_str)
+            .wrap_err_with(|| format!("no signer key given and {path:?} does not contain one"))?;
+        existing
+            .parse::<SignerKey>()
+            .wrap_err_with(|| format!("invalid signer key in {path:?}"))?;
+        if let Err(err) = check_key_file_permissions(path) {
+            warn!(?err, "node config permissions are unsafe");
+        }
+
+        debug!(?path, "using signer key from existing node config");
+        return Ok(());
+    };
+
+    config.insert("key".to_string(), Value::String(key.0.clone()));
+
+    let directory = path
+        .parent()
+        .wrap_err("failed to determine node config directory")?;
+    fs::create_dir_all(directory)
+        .wrap_err_with(|| format!("failed to create node config directory {directory:?}"))?;
+
+    let mut file = NamedTempFile::new_in(directory)?;
+    file.as_file()
+        .set_permissions(Permissions::from_mode(0o600))
+        .wrap_err("failed to restrict node config permissions")?;
+    serde_json::to_writer(&mut file, &config).wrap_err("failed to write node config")?;
+    file.persist(path)
+        .wrap_err("failed to replace node_config.json")?;
+
+    info!(?path, "wrote validator node config");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const KEY: &str = "0x0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn test_parse_signer_key() {
+        assert!(KEY.parse::<SignerKey>().is_ok());
+        assert!(format!(" {KEY}\n").parse::<SignerKey>().is_ok());
+        assert!(KEY[2..].parse::<SignerKey>().is_err());
+        assert!(KEY[..64].parse::<SignerKey>().is_err());
+        assert!(format!("{}zz", &KEY[..64]).parse::<SignerKey>().is_err());
+        assert!(!format!("{:?}", KEY.parse::<SignerKey>().unwrap()).contains(&KEY[2..]));
+
+        let invalid = format!("{}zz", &KEY[..64]);
+        let raw = invalid.parse::<RawSignerKey>().unwrap();
+        assert!(!format!("{raw:?}").contains(&invalid[2..]));
+        assert!(!format!("{:?}", raw.parse().unwrap_err()).contains(&invalid[2..]));
+    }
+
+    #[test]
+    fn test_key_file_permissions() -> eyre::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("signer_key");
+        fs::write(&path, KEY)?;
+
+        fs::set_permissions(&path, Permissions::from_mode(0o644))?;
+        assert!(SignerKey::load(&path).is_err());
+
+        fs::set_permissions(&path, Permissions::from_mode(0o600))?;
+        assert!(SignerKey::load(&path).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prepare_node_config() -> eyre::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("hl/hyperliquid_data/node_config.json");
+
+        assert!(prepare_node_config(&path, None).is_err());
+
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, r#"{"other": 1}"#)?;
+        prepare_node_config(&path, Some(&KEY.parse()?))?;
+
+        let config: Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+        assert_eq!(config["key"], KEY);
+        assert_eq!(config["other"], 1);
+        assert_eq!(fs::metadata(&path)?.mode() & 0o777, 0o600);
+
+        prepare_node_config(&path, None)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,231 @@
+use std::{collections::VecDeque, sync::atomic::Ordering, time::Duration};
+
+use tokio::{
+    sync::mpsc,
+    time::{Instant, MissedTickBehavior, interval},
+};
+use tracing::{info, warn};
+
+use crate::{
+    child::{CHILD_GENERATION, ChildControl},
+    monitor::{GAUGE_HL_NODE_RESPONDING, GAUGE_HL_NODE_TIME_MS, node_drift},
+    rotation::rotate_seed_peers,
+    seed_peers::SeedPeersOptions,
+};
+
+/// How often node state is checked
+const WATCHDOG_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Debug)]
+pub struct WatchdogOptions {
+    /// Restart when drift stays above this for `drift_grace`
+    pub drift_threshold: Option<Duration>,
+    pub drift_grace: Duration,
+    /// Restart when exchange time does not advance for this long
+    pub stall_timeout: Option<Duration>,
+    /// At most this many restarts within `restart_window`
+    pub max_restarts: usize,
+    pub restart_window: Duration,
+}
+
+/// Tracks node progress between checks
+struct WatchdogState {
+    /// hl-visor run progress is tracked for, see [CHILD_GENERATION]
+    generation: u64,
+    drifting_since: Option<Instant>,
+    exchange_time: Option<u64>,
+    /// Last time exchange time moved forward, only tracked once node has responded since start
+    advanced_at: Option<Instant>,
+    restarts: VecDeque<Instant>,
+    rate_limited: bool,
+}
+
+impl WatchdogState {
+    fn new() -> Self {
+        Self {
+            generation: 0,
+            drifting_since: None,
+            exchange_time: None,
+            advanced_at: None,
+            restarts: VecDeque::new(),
+            rate_limited: false,
+        }
+    }
+
+    /// Forgets progress of the previous hl-visor run, whether it was restarted by the watchdog or anything else
+    fn child_started(&mut self, generation: u64) {
+        if generation != self.generation {
+            self.generation = generation;
+            self.reset_progress();
+        }
+    }
+
+    /// Node gets the same time to catch up after restart as after initial start
+    fn reset_progress(&mut self) {
+        self.drifting_since = None;
+        self.exchange_time = None;
+        self.advanced_at = None;
+    }
+
+    /// Returns restart reason when the node is stuck. `exchange_time` is None when the node is not responding
+    fn observe(
+        &mut self,
+        now: Instant,
+        drift: Option<Duration>,
+        exchange_time: Option<u64>,
+        options: &WatchdogOptions,
+    ) -> Option<&'static str> {
+        if let Some(exchange_time) = exchange_time
+            && self.exchange_time.is_none_or(|last| exchange_time > last)
+        {
+            self.exchange_time = Some(exchange_time);
+            self.advanced_at = Some(now);
+        }
+
+        if let Some(stall_timeout) = options.stall_timeout
+            && let Some(advanced_at) = self.advanced_at
+            && now.duration_since(advanced_at) >= stall_timeout
+        {
+            return Some("watchdog: exchange time stalled");
+        }
+
+        match (options.drift_threshold, drift) {
+            (Some(threshold), Some(drift)) if drift > threshold => {
+                let since = *self.drifting_since.get_or_insert(now);
+                (now.duration_since(since) >= options.drift_grace)
+                    .then_some("watchdog: drifting behind")
+            }
+            _ => {
+                self.drifting_since = None;
+                None
+            }
+        }
+    }
+
+    /// Records the restart unless restart rate is exceeded
+    fn try_restart(&mut self, now: Instant, options: &WatchdogOptions) -> bool {
+        while let Some(restart) = self.restarts.front()
+            && now.duration_since(*restart) >= options.restart_window
+        {
+            self.restarts.pop_front();
+        }
+        if self.restarts.len() >= options.max_restarts {
+            return false;
+        }
+
+        self.restarts.push_back(now);
+        self.rate_limited = false;
+        self.reset_progress();
+        true
+    }
+}
+
+/// Worker task restarting hl-visor when the node keeps drifting behind or stops making progress. Seed peers are
+/// re-picked before restarting when `reselect_seed_peers` is given
+pub async fn watchdog_task(
+    options: WatchdogOptions,
+    reselect_seed_peers: Option<SeedPeersOptions>,
+    control: mpsc::Sender<ChildControl>,
+) {
+    let mut interval = interval(WATCHDOG_CHECK_INTERVAL);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    let mut state = WatchdogState::new();
+
+    loop {
+        interval.tick().await;
+
+        state.child_started(CHILD_GENERATION.load(Ordering::Relaxed));
+        let now = Instant::now();
+        let exchange_time =
+            (GAUGE_HL_NODE_RESPONDING.get() == 1).then(|| GAUGE_HL_NODE_TIME_MS.get() as u64);
+        let Some(reason) = state.observe(now, node_drift(), exchange_time, &options) else {
+            continue;
+        };
+
+        if !state.try_restart(now, &options) {
+            if !state.rate_limited {
+                warn!(
+                    reason,
+                    max_restarts = options.max_restarts,
+                    window = ?options.restart_window,
+                    "node is stuck, but watchdog restart limit is reached"
+                );
+            }
+            state.rate_limited = true;
+            continue;
+        }
+
+        warn!(reason, drift = ?node_drift(), "node is stuck, restarting hl-visor");
+        if let Some(seed_peers) = &reselect_seed_peers {
+            info!("re-selecting seed peers before watchdog restart");
+            if let Err(err) = rotate_seed_peers(seed_peers, None).await {
+                warn!(?err, "failed to re-select seed peers");
+            }
+        }
+        if let Err(err) = control.send(ChildControl::Restart { reason }).await {
+            warn!(?err, "failed to request hl-visor restart");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPTIONS: WatchdogOptions = WatchdogOptions {
+        drift_threshold: Some(Duration::from_secs(10)),
+        drift_grace: Duration::from_secs(300),
+        stall_timeout: Some(Duration::from_secs(120)),
+        max_restarts: 2,
+        restart_window: Duration::from_secs(3600),
+    };
+
+    #[test]
+    fn test_watchdog_triggers() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let drift = Some(Duration::from_secs(60));
+        let mut state = WatchdogState::new();
+
+        // Not responding since start is not a stall
+        assert_eq!(state.observe(at(0), None, None, &OPTIONS), None);
+        assert_eq!(state.observe(at(600), None, None, &OPTIONS), None);
+
+        // Drifting, but making progress
+        assert_eq!(state.observe(at(600), drift, Some(1000), &OPTIONS), None);
+        assert_eq!(state.observe(at(700), drift, Some(2000), &OPTIONS), None);
+        assert_eq!(
+            state.observe(at(900), drift, Some(3000), &OPTIONS),
+            Some("watchdog: drifting behind")
+        );
+
+        assert!(state.try_restart(at(900), &OPTIONS));
+        assert_eq!(state.observe(at(910), None, Some(3000), &OPTIONS), None);
+        assert_eq!(state.observe(at(1000), None, Some(3000), &OPTIONS), None);
+        assert_eq!(
+            state.observe(at(1030), None, None, &OPTIONS),
+            Some("watchdog: exchange time stalled")
+        );
+
+        // hl-visor restarted by supervisor or rotation gets the same time to catch up
+        state.child_started(1);
+        assert_eq!(state.observe(at(1040), drift, None, &OPTIONS), None);
+        assert_eq!(state.observe(at(1300), drift, Some(4000), &OPTIONS), None);
+        state.child_started(2);
+        assert_eq!(state.observe(at(1400), drift, Some(100), &OPTIONS), None);
+        assert_eq!(state.observe(at(1500), drift, Some(100), &OPTIONS), None);
+    }
+
+    #[test]
+    fn test_watchdog_restart_rate() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut state = WatchdogState::new();
+
+        assert!(state.try_restart(at(0), &OPTIONS));
+        assert!(state.try_restart(at(1000), &OPTIONS));
+        assert!(!state.try_restart(at(2000), &OPTIONS));
+        assert!(state.try_restart(at(3600), &OPTIONS));
+        assert!(!state.try_restart(at(3700), &OPTIONS));
+    }
+}